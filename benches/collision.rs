@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruspect::{
+    map::{calc_mid_room_pos, TILE_SIZE, X_MAP_LENGTH, X_ROOM_LENGTH, Y_MAP_LENGTH, Y_ROOM_LENGTH},
+    spawnable::{
+        behavior::Spawnable,
+        collision::{CollisionEvent, CollisionMatrix},
+        movement::{movement_and_collision, Collider, MoveEntity, Movement},
+        player::Player,
+    },
+};
+
+// --- Constants ---
+/// Number of bullets which are moved every frame
+const BULLET_COUNT: usize = 200;
+/// Speed of the bullets
+const BULLET_SPEED: f32 = 15.0;
+
+// --- Components ---
+/// Marker for the bullets of the benchmark
+#[derive(Component)]
+struct BenchBullet {
+    direction: Vec2,
+}
+
+// --- Systems ---
+/// Advance the time like the core plugin does
+fn update_time(mut time: ResMut<Time>) {
+    time.update();
+}
+
+/// Give every bullet a movement like the bullet plugin does
+fn fire_bullets(mut commands: Commands, query: Query<(&BenchBullet, &Transform, Entity)>) {
+    for (bullet, transform, entity) in query.iter() {
+        commands.entity(entity).insert(Movement {
+            direction: bullet.direction,
+            transform: *transform,
+            speed: BULLET_SPEED,
+        });
+    }
+}
+
+// --- Functions ---
+/// Spawn the walls of a room, like a map has them for every room
+fn spawn_room_walls(world: &mut World, x: i32, y: i32) {
+    let offset = calc_mid_room_pos(x, y)
+        - Vec2::new(X_ROOM_LENGTH as f32, Y_ROOM_LENGTH as f32) * TILE_SIZE / 2.0
+        + TILE_SIZE / 2.0;
+
+    for tile_y in 0..Y_ROOM_LENGTH {
+        for tile_x in 0..X_ROOM_LENGTH {
+            if tile_x == 0
+                || tile_y == 0
+                || tile_x == X_ROOM_LENGTH - 1
+                || tile_y == Y_ROOM_LENGTH - 1
+            {
+                world
+                    .spawn()
+                    .insert(Transform {
+                        translation: (offset + Vec2::new(tile_x as f32, tile_y as f32) * TILE_SIZE)
+                            .extend(-1.0),
+                        scale: Vec3::new(TILE_SIZE, TILE_SIZE, 0.0),
+                        ..Default::default()
+                    })
+                    .insert(Collider::Solid);
+            }
+        }
+    }
+}
+
+/// Create a world with the walls of a full map, the player and the bullets in the start room
+fn setup_app() -> App {
+    let mut app = App::new();
+    let collision_matrix =
+        ron::de::from_bytes::<CollisionMatrix>(include_bytes!("../assets/resources/collision.ron"))
+            .unwrap();
+
+    app.insert_resource(Time::default())
+        .insert_resource(collision_matrix)
+        .add_event::<MoveEntity>()
+        .add_event::<CollisionEvent>()
+        .add_system(update_time.label("time"))
+        .add_system(fire_bullets.label("fire").after("time"))
+        .add_stage_after(
+            CoreStage::Update,
+            "collision",
+            SystemStage::single(movement_and_collision),
+        );
+
+    for y in 0..Y_MAP_LENGTH {
+        for x in 0..X_MAP_LENGTH {
+            spawn_room_walls(&mut app.world, x, y);
+        }
+    }
+
+    let start_room_mid = calc_mid_room_pos(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
+
+    app.world
+        .spawn()
+        .insert(Transform {
+            translation: start_room_mid.extend(0.0),
+            scale: Vec3::new(25.0, 25.0, 0.0),
+            ..Default::default()
+        })
+        .insert(Player {
+            score: 0,
+            speed: 20.0,
+            color: Color::rgb(0.0, 0.0, 1.0),
+        })
+        .insert(Collider::Player);
+
+    // Spread the bullets over the room, flying in all directions
+    for i in 0..BULLET_COUNT {
+        let angle = i as f32 / BULLET_COUNT as f32 * std::f32::consts::TAU;
+        let distance = (i % 10 + 2) as f32 * TILE_SIZE / 2.0;
+
+        app.world
+            .spawn()
+            .insert(Transform {
+                translation: (start_room_mid + Vec2::new(angle.cos(), angle.sin()) * distance)
+                    .extend(0.0),
+                scale: Vec3::new(5.0, 5.0, 0.0),
+                ..Default::default()
+            })
+            .insert(BenchBullet {
+                direction: Vec2::new(angle.cos(), angle.sin()),
+            })
+            .insert(Spawnable {
+                on_despawn: vec![],
+                despawn: false,
+            })
+            .insert(Collider::PlayerBullet);
+    }
+
+    app
+}
+
+/// Measure the cost of a frame of the movement and collision with 200 bullets
+fn bench_collision(c: &mut Criterion) {
+    let mut app = setup_app();
+
+    c.bench_function("movement_and_collision 200 bullets", |b| {
+        b.iter(|| app.update())
+    });
+}
+
+criterion_group!(benches, bench_collision);
+criterion_main!(benches);
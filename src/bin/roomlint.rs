@@ -0,0 +1,317 @@
+//! Checks every room file in `assets/rooms`
+//!
+//! Reports wrong dimensions, unknown characters and blocked doors with their line and column,
+//! and exits with an error code if any room file is invalid.
+//!
+//! Run it from the root of the repository with `cargo run --bin roomlint`.
+
+// --- Imports ---
+use bevy::prelude::Vec2;
+use ron::de::from_str;
+use ruspect::map::{
+    pathfinding::{find_path, NavigationGrid, TilePos},
+    room::TileType,
+    room_generation::{parse_room, RoomHeader, HEADER_SEPARATOR, TILE_GLYPHS},
+    room_library::ROOM_DIRECTORY,
+    X_ROOM_LENGTH, Y_ROOM_LENGTH,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+// --- Constants ---
+/// Directory of the start rooms, the player only spawns in these rooms
+const START_ROOM_DIRECTORY: &str = "assets/rooms/start";
+/// Number of columns of a room file, the surrounding walls are added by the game
+const MAP_WIDTH: usize = X_ROOM_LENGTH - 2;
+/// Number of rows of a room file, the surrounding walls are added by the game
+const MAP_HEIGHT: usize = Y_ROOM_LENGTH - 2;
+
+// --- Structs ---
+/// Error in a room file
+///
+/// * `line` - Line of the error, starting at 1
+/// * `column` - Column of the error, starting at 1
+/// * `message` - Description of the error
+#[derive(Debug)]
+struct LintError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+fn main() {
+    let mut files = vec![];
+    if let Err(err) = collect_room_files(Path::new(ROOM_DIRECTORY), &mut files) {
+        println!("Could not read {}: {}", ROOM_DIRECTORY, err);
+        exit(2);
+    }
+
+    let mut error_count = 0;
+    for file in files.iter() {
+        let errors = match fs::read_to_string(file) {
+            Ok(text) => lint_room(&text, file.starts_with(START_ROOM_DIRECTORY)),
+            Err(err) => vec![LintError {
+                line: 1,
+                column: 1,
+                message: format!("Could not read the file: {}", err),
+            }],
+        };
+
+        for error in errors.iter() {
+            println!(
+                "{}:{}:{}: {}",
+                file.display(),
+                error.line,
+                error.column,
+                error.message
+            );
+        }
+        error_count += errors.len();
+    }
+
+    if error_count > 0 {
+        println!("{} error(s) in {} room files", error_count, files.len());
+        exit(1);
+    }
+    println!("All {} room files are valid", files.len());
+}
+
+// --- Functions ---
+/// Collect the room files of a directory and its subdirectories, sorted by their path
+///
+/// # Arguments
+/// * `directory` - The directory to search
+/// * `files` - The list the room files are added to
+fn collect_room_files(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_room_files(&path, files)?;
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "txt")
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a room file
+///
+/// * The header must be valid and its legend must not use the characters of the tiles
+/// * The map must have the size of a room without its surrounding walls
+/// * Every character must be a tile or a spawner of the legend
+/// * The tiles in front of the four doors and the spawn point in the center of a start room
+///   must be reachable from each other. Rocks can be destroyed by the player, so they don't block the way.
+///
+/// # Arguments
+/// * `text` - The content of the room file
+/// * `has_spawn_point` - If the player spawns in the room
+///
+/// # Returns
+/// All errors of the room file
+fn lint_room(text: &str, has_spawn_point: bool) -> Vec<LintError> {
+    let mut errors = vec![];
+    let lines: Vec<&str> = text.lines().collect();
+
+    // Header with the legend
+    let (legend, map_start) = match lines
+        .iter()
+        .position(|line| line.trim() == HEADER_SEPARATOR)
+    {
+        Some(separator) => match from_str::<RoomHeader>(&lines[..separator].join("\n")) {
+            Ok(header) => (header.spawners.keys().copied().collect(), separator + 1),
+            Err(err) => {
+                errors.push(LintError {
+                    line: err.span.start.line,
+                    column: err.span.start.col,
+                    message: format!("Invalid header: {}", err.code),
+                });
+                return errors;
+            }
+        },
+        None => (vec![], 0),
+    };
+
+    for glyph in legend.iter().filter(|glyph| TILE_GLYPHS.contains(glyph)) {
+        errors.push(LintError {
+            line: 1,
+            column: 1,
+            message: format!("Legend character '{}' is already a tile", glyph),
+        });
+    }
+
+    // Size and characters of the map, empty lines at the end are ignored
+    let mut map_lines = &lines[map_start..];
+    while let Some((last, rest)) = map_lines.split_last() {
+        if !last.trim().is_empty() {
+            break;
+        }
+        map_lines = rest;
+    }
+
+    if map_lines.len() != MAP_HEIGHT {
+        errors.push(LintError {
+            line: map_start + usize::min(map_lines.len(), MAP_HEIGHT) + 1,
+            column: 1,
+            message: format!("Expected {} rows, found {}", MAP_HEIGHT, map_lines.len()),
+        });
+    }
+
+    for (y, line) in map_lines.iter().enumerate() {
+        let width = line.chars().count();
+        if width != MAP_WIDTH {
+            errors.push(LintError {
+                line: map_start + y + 1,
+                column: usize::min(width, MAP_WIDTH) + 1,
+                message: format!("Expected {} columns, found {}", MAP_WIDTH, width),
+            });
+        }
+
+        for (x, character) in line.chars().enumerate() {
+            if !TILE_GLYPHS.contains(&character) && !legend.contains(&character) {
+                errors.push(LintError {
+                    line: map_start + y + 1,
+                    column: x + 1,
+                    message: format!("Unknown character '{}'", character),
+                });
+            }
+        }
+    }
+
+    // Reachability of the spawn point and the doors
+    let room = match parse_room(text) {
+        Ok(room) => room,
+        Err(err) => {
+            errors.push(LintError {
+                line: 1,
+                column: 1,
+                message: format!("Invalid room: {}", err),
+            });
+            return errors;
+        }
+    };
+
+    let tiles = room
+        .tiles
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|tile| match tile {
+                    TileType::Rock => TileType::Empty,
+                    tile => tile,
+                })
+                .collect()
+        })
+        .collect();
+    let grid = NavigationGrid::new(tiles, Vec2::ZERO);
+
+    let mut points = vec![
+        (
+            "top door",
+            TilePos::new(MAP_WIDTH as i32 / 2, MAP_HEIGHT as i32 - 1),
+        ),
+        ("bottom door", TilePos::new(MAP_WIDTH as i32 / 2, 0)),
+        ("left door", TilePos::new(0, MAP_HEIGHT as i32 / 2)),
+        (
+            "right door",
+            TilePos::new(MAP_WIDTH as i32 - 1, MAP_HEIGHT as i32 / 2),
+        ),
+    ];
+    if has_spawn_point {
+        points.insert(
+            0,
+            (
+                "spawn point",
+                TilePos::new(MAP_WIDTH as i32 / 2, MAP_HEIGHT as i32 / 2),
+            ),
+        );
+    }
+    let position = |tile: TilePos| {
+        (
+            map_start + MAP_HEIGHT - tile.y as usize,
+            tile.x as usize + 1,
+        )
+    };
+
+    // The paths are the same in both directions, so every point is checked from the first free one
+    let mut start: Option<(&str, TilePos)> = None;
+    for (name, tile) in points {
+        let (line, column) = position(tile);
+
+        if !grid.is_walkable(tile) {
+            errors.push(LintError {
+                line,
+                column,
+                message: format!("The {} is blocked", name),
+            });
+            continue;
+        }
+
+        match start {
+            None => start = Some((name, tile)),
+            Some((start_name, start_tile)) => {
+                if find_path(&grid, start_tile, tile).is_none() {
+                    errors.push(LintError {
+                        line,
+                        column,
+                        message: format!("The {} can't be reached from the {}", name, start_name),
+                    });
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if all shipped room files are valid
+    #[test]
+    fn test_shipped_rooms() {
+        let mut files = vec![];
+        collect_room_files(Path::new(ROOM_DIRECTORY), &mut files).unwrap();
+        assert!(!files.is_empty());
+
+        for file in files {
+            let errors = lint_room(
+                &fs::read_to_string(&file).unwrap(),
+                file.starts_with(START_ROOM_DIRECTORY),
+            );
+            assert!(errors.is_empty(), "{}: {:?}", file.display(), errors);
+        }
+    }
+
+    /// Test if short lines, unknown characters and blocked doors are found
+    #[test]
+    fn test_invalid_room() {
+        let mut lines = vec!["#".repeat(MAP_WIDTH); MAP_HEIGHT];
+        lines[0] = "#".repeat(MAP_WIDTH - 2);
+        lines[1].replace_range(3..4, "?");
+        lines[MAP_HEIGHT / 2].replace_range(0..1, "X");
+
+        let errors = lint_room(&lines.join("\n"), true);
+        let messages: Vec<(usize, usize)> = errors
+            .iter()
+            .map(|error| (error.line, error.column))
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![(1, MAP_WIDTH - 1), (2, 4), (MAP_HEIGHT / 2 + 1, 1)]
+        );
+        assert_eq!(errors[2].message, "The left door is blocked");
+    }
+}
@@ -2,8 +2,12 @@
 use bevy::prelude::*;
 
 use crate::{
-    map::{calc_mid_room_pos, room::LeaveRoomEvent, X_MAP_LENGTH, Y_MAP_LENGTH},
+    map::{
+        calc_mid_room_pos, calc_room_pos, map_generation::RoomPos, room::LeaveRoomEvent,
+        X_MAP_LENGTH, Y_MAP_LENGTH,
+    },
     menu::AppState,
+    save::RunSave,
     PlayerCamera,
 };
 
@@ -19,14 +23,19 @@ impl Plugin for CameraPlugin {
 // --- System-Functions ---
 /// Initialize the camera
 ///
-/// Initializes the camera to the middle of the map
-fn setup(mut commands: Commands) {
+/// Initializes the camera to the middle of the map, or to the room of the player if a saved run is continued
+fn setup(mut commands: Commands, resume: Option<Res<RunSave>>) {
     // camera
     let mut camera = OrthographicCameraBundle::new_2d();
     camera.transform.translation = Vec3::new(0., 0., 100.0);
     camera.transform.scale = Vec3::new(1.5, 1.5, 1.0);
 
-    let curr_room_pos = calc_mid_room_pos(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
+    let room_pos = match resume {
+        Some(save) => calc_room_pos(save.player.position),
+        None => RoomPos::new(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2),
+    };
+
+    let curr_room_pos = calc_mid_room_pos(room_pos.x, room_pos.y);
     camera.transform.translation = Vec3::new(curr_room_pos.x, curr_room_pos.y, 100.0);
 
     let mut camera_entity = commands.spawn_bundle(camera);
@@ -50,3 +59,30 @@ fn room_transit(
         transform.translation = Vec3::new(curr_room_pos.x, curr_room_pos.y, 100.0);
     }
 }
+
+// --- Functions ---
+/// Project the cursor into the world
+///
+/// The cursor is projected through the projection and the transform of the camera, so the scale of the camera is respected.
+///
+/// # Arguments
+/// * `window` - The window of the cursor
+/// * `camera` - The camera which renders the window
+/// * `camera_transform` - Transform of the camera
+///
+/// # Returns
+/// The world position of the cursor, or None if the cursor isn't in the window
+pub fn cursor_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let window_size = Vec2::new(window.width(), window.height());
+
+    // Cursor position from -1 to 1, as it is seen by the projection
+    let ndc = (cursor / window_size) * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+
+    Some(ndc_to_world.project_point3(ndc.extend(-1.0)).truncate())
+}
@@ -0,0 +1,223 @@
+// --- Imports ---
+use bevy::{input::InputSystem, prelude::*};
+use ron::de::from_bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+};
+
+// --- Plugin declaration ---
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .add_startup_system(load_user_bindings)
+            .add_system_to_stage(CoreStage::PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+// --- Constants ---
+/// Path of the user config which overrides the default bindings
+pub const USER_BINDINGS_PATH: &str = "config/bindings.ron";
+/// Stick values below the deadzone are ignored
+const AXIS_DEADZONE: f32 = 0.2;
+/// Value from which an action counts as pressed, so sticks can be used for digital actions
+const PRESS_THRESHOLD: f32 = 0.5;
+
+// --- Components and Structs ---
+/// Actions of the game which can be bound to inputs
+///
+/// * `MoveUp`, `MoveDown`, `MoveLeft`, `MoveRight` - Moves the player
+/// * `ShootUp`, `ShootDown`, `ShootLeft`, `ShootRight` - Shoots the active weapon
+/// * `Shoot` - Shoots the active weapon at the cursor, if mouse aim is turned on
+/// * `PreviousWeapon`, `NextWeapon` - Switches the active weapon
+/// * `Dodge` - Rolls in the direction of the movement
+/// * `MenuUp`, `MenuDown` - Changes the selection in a menu
+/// * `Confirm` - Chooses the selected option of a menu
+/// * `Back` - Leaves a menu page or the game from the main menu
+/// * `Pause` - Pauses or resumes the game
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ShootUp,
+    ShootDown,
+    ShootLeft,
+    ShootRight,
+    Shoot,
+    PreviousWeapon,
+    NextWeapon,
+    Dodge,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    Back,
+    Pause,
+}
+
+/// Input which triggers an action
+///
+/// * `Key` - A keyboard key
+/// * `Mouse` - A mouse button
+/// * `Button` - A button of any gamepad
+/// * `Axis` - A stick axis of any gamepad, the value is multiplied with the factor, so a negative factor binds the negative direction
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Button(GamepadButtonType),
+    Axis(GamepadAxisType, f32),
+}
+
+/// Bindings of all actions, loaded from `bindings.ron` and the user config
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InputBindings {
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+/// Current state of all actions, updated every frame before the game systems run
+///
+/// Analog inputs have a value between 0 and 1, digital inputs are either 0 or 1.
+#[derive(Default)]
+pub struct ActionState {
+    values: HashMap<Action, f32>,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+// --- Methods for Structs ---
+impl InputBindings {
+    /// Replace the bindings of every action which is bound in the other bindings
+    ///
+    /// # Arguments
+    /// * `other` - Bindings which take priority, actions which aren't bound there keep their bindings
+    pub fn merge(&mut self, other: InputBindings) {
+        self.bindings.extend(other.bindings);
+    }
+}
+
+impl ActionState {
+    /// Get the value of an action between 0 and 1
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    /// Check if an action is pressed
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Check if an action was pressed in this frame
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Check if any of the actions was pressed in this frame
+    pub fn any_just_pressed(&self, actions: impl IntoIterator<Item = Action>) -> bool {
+        actions.into_iter().any(|action| self.just_pressed(action))
+    }
+
+    /// Get the direction of four actions, analog inputs keep their strength
+    ///
+    /// # Arguments
+    /// * `up`, `down`, `left`, `right` - Actions of the four directions
+    pub fn direction(&self, up: Action, down: Action, left: Action, right: Action) -> Vec2 {
+        Vec2::new(
+            self.value(right) - self.value(left),
+            self.value(up) - self.value(down),
+        )
+    }
+
+    /// Forget the actions which were pressed in this frame
+    ///
+    /// Used after changing the state, so the systems of the new state don't react to the same input.
+    pub fn reset_just_pressed(&mut self) {
+        self.just_pressed.clear();
+    }
+}
+
+// --- System-Functions ---
+/// Applies the bindings of the user config to the default bindings
+fn load_user_bindings(mut bindings: ResMut<InputBindings>) {
+    match fs::read(USER_BINDINGS_PATH) {
+        Ok(bytes) => match from_bytes::<InputBindings>(&bytes) {
+            Ok(user_bindings) => bindings.merge(user_bindings),
+            Err(err) => println!("Error loading {}: {}", USER_BINDINGS_PATH, err),
+        },
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                println!("Error reading {}: {}", USER_BINDINGS_PATH, err);
+            }
+        }
+    }
+}
+
+/// Updates the state of all actions from the keyboard, the mouse and the connected gamepads
+fn update_action_state(
+    bindings: Res<InputBindings>,
+    (keys, mouse): (Res<Input<KeyCode>>, Res<Input<MouseButton>>),
+    (gamepads, buttons, axes): (
+        Res<Gamepads>,
+        Res<Input<GamepadButton>>,
+        Res<Axis<GamepadAxis>>,
+    ),
+    mut action_state: ResMut<ActionState>,
+) {
+    let previously_pressed = std::mem::take(&mut action_state.pressed);
+    action_state.values.clear();
+    action_state.just_pressed.clear();
+
+    for (&action, action_bindings) in bindings.bindings.iter() {
+        let value = action_bindings
+            .iter()
+            .map(|binding| binding_value(binding, (&keys, &mouse), &gamepads, &buttons, &axes))
+            .fold(0.0, f32::max);
+        if value <= 0.0 {
+            continue;
+        }
+
+        action_state.values.insert(action, value);
+        if value >= PRESS_THRESHOLD {
+            action_state.pressed.insert(action);
+            if !previously_pressed.contains(&action) {
+                action_state.just_pressed.insert(action);
+            }
+        }
+    }
+}
+
+// --- Functions ---
+/// Get the value of a binding between 0 and 1, the highest value of all gamepads is used
+///
+/// # Arguments
+/// * `binding` - The binding to check
+/// * `keys`, `mouse` - State of the keyboard and the mouse
+/// * `gamepads` - Connected gamepads
+/// * `buttons` - State of the gamepad buttons
+/// * `axes` - State of the gamepad axes
+fn binding_value(
+    binding: &Binding,
+    (keys, mouse): (&Input<KeyCode>, &Input<MouseButton>),
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> f32 {
+    match *binding {
+        Binding::Key(key) => keys.pressed(key) as i32 as f32,
+        Binding::Mouse(button) => mouse.pressed(button) as i32 as f32,
+        Binding::Button(button_type) => gamepads
+            .iter()
+            .any(|&gamepad| buttons.pressed(GamepadButton(gamepad, button_type)))
+            as i32 as f32,
+        Binding::Axis(axis_type, factor) => gamepads
+            .iter()
+            .filter_map(|&gamepad| axes.get(GamepadAxis(gamepad, axis_type)))
+            .map(|value| (value * factor).clamp(0.0, 1.0))
+            .filter(|&value| value >= AXIS_DEADZONE)
+            .fold(0.0, f32::max),
+    }
+}
@@ -0,0 +1,459 @@
+// --- Imports ---
+use anyhow::anyhow;
+use bevy::{
+    asset::{Asset, AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+};
+use ron::de::from_bytes;
+use std::collections::HashSet;
+
+use crate::{
+    map::room_library::RoomLibrary,
+    spawnable::{
+        behavior::DespawnBehavior,
+        enemy::{
+            behavior::EnemyBehavior,
+            enemy_types::{EnemyResource, EnemyType},
+        },
+        item::{ItemResource, StatModifier, ITEM_TYPES},
+        weapon::{WeaponResource, WeaponTypes, PLAYER_WEAPONS},
+    },
+};
+
+// --- Plugin declaration ---
+pub struct GameDataPlugin;
+
+impl Plugin for GameDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<EnemyResource>()
+            .add_asset::<WeaponResource>()
+            .init_asset_loader::<GameDataLoader>()
+            .add_startup_system(load_game_data)
+            .add_system(update_game_data);
+    }
+}
+
+// --- Constants ---
+/// Path of the enemies in the asset folder
+pub const ENEMY_DATA_PATH: &str = "resources/enemy.ron";
+/// Path of the weapons in the asset folder
+pub const WEAPON_DATA_PATH: &str = "resources/weapon.ron";
+
+/// Deepest floor whose enemy pool is checked, deeper floors don't add enemies
+const DEEPEST_POOL_FLOOR: i32 = 3;
+
+// --- Structs ---
+/// Problems which were found by the validation of the game data
+///
+/// * `errors` - Problems which would crash the game or break an enemy, like missing keys
+/// * `warnings` - Definitions which can never be used
+#[derive(Default, Debug)]
+pub struct ValidationReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// --- Methods for Structs ---
+impl ValidationReport {
+    /// Print every error and warning of the report
+    pub fn print(&self) {
+        for error in self.errors.iter() {
+            println!("Error in the game data: {}", error);
+        }
+        for warning in self.warnings.iter() {
+            println!("Warning in the game data: {}", warning);
+        }
+    }
+}
+
+// --- Resources ---
+/// Handles of the game data files, which keep the loaded files alive
+pub struct GameDataHandles {
+    pub enemies: Handle<EnemyResource>,
+    pub weapons: Handle<WeaponResource>,
+}
+
+// --- Asset loader ---
+/// Loads the enemies and the weapons from their ron files in the asset folder
+///
+/// Both files have the same extension, so the type is chosen by the name of the file.
+/// If a file can't be parsed, the error is logged by the asset server and the last good version stays in use.
+#[derive(Default)]
+pub struct GameDataLoader;
+
+impl AssetLoader for GameDataLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().to_owned();
+            let error = |err| anyhow!("Error loading {}: {}", path.display(), err);
+
+            match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some("enemy") => load_context.set_default_asset(LoadedAsset::new(
+                    from_bytes::<EnemyResource>(bytes).map_err(error)?,
+                )),
+                Some("weapon") => load_context.set_default_asset(LoadedAsset::new(
+                    from_bytes::<WeaponResource>(bytes).map_err(error)?,
+                )),
+                _ => return Err(anyhow!("{} is no game data file", path.display())),
+            }
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+// --- System-Functions ---
+/// Loads the game data files and watches them for changes
+///
+/// Until the files are loaded, the versions which are built into the game are used.
+fn load_game_data(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if let Err(err) = asset_server.watch_for_changes() {
+        println!("Game data won't be reloaded: {:?}", err);
+    }
+
+    commands.insert_resource(GameDataHandles {
+        enemies: asset_server.load(ENEMY_DATA_PATH),
+        weapons: asset_server.load(WEAPON_DATA_PATH),
+    });
+}
+
+/// Replaces the game data resources when their files were loaded or changed
+///
+/// The new data is only used if the validation finds no errors, otherwise the last good version stays in use.
+/// Enemies and weapons which are spawned afterwards use the new data.
+fn update_game_data(
+    (mut ev_enemies, mut ev_weapons): (
+        EventReader<AssetEvent<EnemyResource>>,
+        EventReader<AssetEvent<WeaponResource>>,
+    ),
+    (enemy_assets, weapon_assets): (Res<Assets<EnemyResource>>, Res<Assets<WeaponResource>>),
+    mut enemy_res: ResMut<EnemyResource>,
+    mut weapon_res: ResMut<WeaponResource>,
+    room_library: Res<RoomLibrary>,
+) {
+    let enemies = ev_enemies
+        .iter()
+        .filter_map(changed_handle)
+        .filter_map(|handle| enemy_assets.get(handle))
+        .next_back();
+    let weapons = ev_weapons
+        .iter()
+        .filter_map(changed_handle)
+        .filter_map(|handle| weapon_assets.get(handle))
+        .next_back();
+    if enemies.is_none() && weapons.is_none() {
+        return;
+    }
+
+    let enemies = enemies.unwrap_or(&enemy_res);
+    let weapons = weapons.unwrap_or(&weapon_res);
+    let report = validate_game_data(enemies, weapons, &room_library);
+    report.print();
+
+    if report.errors.is_empty() {
+        let (enemies, weapons) = (enemies.clone(), weapons.clone());
+        *enemy_res = enemies;
+        *weapon_res = weapons;
+    }
+}
+
+// --- Functions ---
+/// Get the handle of a loaded or changed asset
+fn changed_handle<T: Asset>(ev: &AssetEvent<T>) -> Option<&Handle<T>> {
+    match ev {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+        AssetEvent::Removed { .. } => None,
+    }
+}
+
+/// Check the references between the enemies and the weapons and the invariants of their values
+///
+/// Enemies are reachable if they are in the enemy pool of a floor or the legend of a room, the boss
+/// or spawned by a reachable enemy.
+/// Weapons are reachable if they are a player weapon or used by a reachable enemy.
+///
+/// # Arguments
+/// * `enemy_res` - The enemies to check
+/// * `weapon_res` - The weapons to check
+/// * `room_library` - The rooms whose legends spawn enemies
+///
+/// # Returns
+/// Every missing key and broken invariant as an error, every unreachable definition as a warning
+pub fn validate_game_data(
+    enemy_res: &EnemyResource,
+    weapon_res: &WeaponResource,
+    room_library: &RoomLibrary,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // Follow the spawned enemies from the enemies which are spawned by the rooms
+    let mut open = EnemyType::pool_for_floor(DEEPEST_POOL_FLOOR);
+    open.push(EnemyType::Boss);
+    for (name, enemy_type) in room_library.legend_enemies() {
+        if enemy_res.enemys.contains_key(enemy_type) {
+            open.push(enemy_type.clone());
+        } else {
+            report.errors.push(format!(
+                "Enemy {:?} is spawned by room {}, but not defined",
+                enemy_type, name
+            ));
+        }
+    }
+    let mut reachable_enemies: HashSet<EnemyType> = HashSet::new();
+    let mut reachable_weapons: HashSet<WeaponTypes> = PLAYER_WEAPONS.iter().cloned().collect();
+
+    while let Some(enemy_type) = open.pop() {
+        if !reachable_enemies.insert(enemy_type.clone()) {
+            continue;
+        }
+
+        let enemy = match enemy_res.enemys.get(&enemy_type) {
+            Some(enemy) => enemy,
+            None => {
+                report
+                    .errors
+                    .push(format!("Enemy {:?} is used, but not defined", enemy_type));
+                continue;
+            }
+        };
+
+        for behavior in enemy.behavior.on_despawn.iter() {
+            if let DespawnBehavior::SpawnNewMob(spawned) = behavior {
+                open.push(spawned.clone());
+            }
+        }
+
+        // Weapons with the behaviors which use them, bosses replace their weapons in every phase
+        let mut weapon_lists = vec![(&enemy.data.weapon, &enemy.data.behavior)];
+        for state in enemy.data.states.iter() {
+            weapon_lists.push((&enemy.data.weapon, &state.behavior));
+        }
+        for phase in enemy.data.phases.iter() {
+            weapon_lists.push((&phase.weapons, &phase.movement));
+
+            if phase.attacks.is_empty() {
+                report.errors.push(format!(
+                    "A boss phase of enemy {:?} has no attacks",
+                    enemy_type
+                ));
+            }
+            for attack in phase.attacks.iter() {
+                if attack.weapon >= phase.weapons.len() {
+                    report.errors.push(format!(
+                        "A boss attack of enemy {:?} fires weapon {}, but the phase has {} weapons",
+                        enemy_type,
+                        attack.weapon,
+                        phase.weapons.len()
+                    ));
+                }
+            }
+        }
+
+        for (weapons, behaviors) in weapon_lists {
+            reachable_weapons.extend(weapons.iter().cloned());
+
+            let has_weapons = !weapons.is_empty() && !weapons.contains(&WeaponTypes::None);
+            let shoots = behaviors
+                .iter()
+                .any(|behavior| matches!(behavior, EnemyBehavior::ShootAtPlayer));
+            if shoots && !has_weapons {
+                report.errors.push(format!(
+                    "Enemy {:?} shoots at the player without a weapon",
+                    enemy_type
+                ));
+            }
+        }
+
+        if enemy.health.max_health <= 0.0 {
+            report
+                .errors
+                .push(format!("Enemy {:?} has no maximum health", enemy_type));
+        }
+    }
+
+    // The None weapon is no weapon and has no definition
+    reachable_weapons.remove(&WeaponTypes::None);
+    for weapon_type in reachable_weapons.iter() {
+        if !weapon_res.weapons.contains_key(weapon_type) {
+            report
+                .errors
+                .push(format!("Weapon {:?} is used, but not defined", weapon_type));
+        }
+    }
+
+    for enemy_type in enemy_res.enemys.keys() {
+        if !reachable_enemies.contains(enemy_type) {
+            report.warnings.push(format!(
+                "Enemy {:?} is defined, but never spawned",
+                enemy_type
+            ));
+        }
+    }
+    for (weapon_type, weapon) in weapon_res.weapons.iter() {
+        if weapon.shooting_pattern.is_empty() {
+            report
+                .errors
+                .push(format!("Weapon {:?} has no shooting pattern", weapon_type));
+        }
+        if !reachable_weapons.contains(weapon_type) {
+            report.warnings.push(format!(
+                "Weapon {:?} is defined, but never used",
+                weapon_type
+            ));
+        }
+    }
+
+    // Hash sets have no order, so the report is sorted to be readable
+    report.errors.sort();
+    report.warnings.sort();
+    report
+}
+
+/// Check that every item is defined and the invariants of their modifiers
+///
+/// The items aren't reloaded, so they are only checked when the game starts.
+///
+/// # Arguments
+/// * `item_res` - The items to check
+///
+/// # Returns
+/// Every missing item and broken modifier as an error, every item without modifiers as a warning
+pub fn validate_item_data(item_res: &ItemResource) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // Collected items are saved with the run, so every item type needs a definition
+    for item_type in ITEM_TYPES.iter() {
+        if !item_res.items.contains_key(item_type) {
+            report
+                .errors
+                .push(format!("Item {:?} is not defined", item_type));
+        }
+    }
+
+    for (item_type, item) in item_res.items.iter() {
+        if item.modifiers.is_empty() {
+            report
+                .warnings
+                .push(format!("Item {:?} has no modifiers", item_type));
+        }
+
+        for modifier in item.modifiers.iter() {
+            let factor = match modifier {
+                StatModifier::Damage(factor)
+                | StatModifier::FireRate(factor)
+                | StatModifier::BulletSpeed(factor)
+                | StatModifier::Spread(factor) => *factor,
+                StatModifier::ShootingPattern(shooting_pattern) => {
+                    if shooting_pattern.is_empty() {
+                        report.errors.push(format!(
+                            "Item {:?} replaces the shooting pattern with an empty one",
+                            item_type
+                        ));
+                    }
+                    continue;
+                }
+                StatModifier::Speed(_) | StatModifier::MaxHealth(_) => continue,
+            };
+
+            if factor <= 0.0 {
+                report.errors.push(format!(
+                    "Item {:?} multiplies a weapon stat by {}",
+                    item_type, factor
+                ));
+            }
+        }
+    }
+
+    // Hash maps have no order, so the report is sorted to be readable
+    report.errors.sort();
+    report.warnings.sort();
+    report
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+    use std::path::Path;
+
+    /// Test if the shipped enemies and weapons are valid and all of them are used
+    #[test]
+    fn test_validate_shipped_game_data() {
+        let enemy_res =
+            from_bytes::<EnemyResource>(include_bytes!("../assets/resources/enemy.ron")).unwrap();
+        let weapon_res =
+            from_bytes::<WeaponResource>(include_bytes!("../assets/resources/weapon.ron")).unwrap();
+        let room_library = RoomLibrary::load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+
+        let report = validate_game_data(&enemy_res, &weapon_res, &room_library);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    /// Test if an enemy of a room legend which isn't defined is found
+    #[test]
+    fn test_validate_legend_enemies() {
+        let mut enemy_res =
+            from_bytes::<EnemyResource>(include_bytes!("../assets/resources/enemy.ron")).unwrap();
+        let weapon_res =
+            from_bytes::<WeaponResource>(include_bytes!("../assets/resources/weapon.ron")).unwrap();
+        let room_library = RoomLibrary::load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+
+        let (name, enemy_type) = room_library.legend_enemies()[0];
+        enemy_res.enemys.remove(enemy_type);
+
+        let report = validate_game_data(&enemy_res, &weapon_res, &room_library);
+
+        assert!(report.errors.contains(&format!(
+            "Enemy {:?} is spawned by room {}, but not defined",
+            enemy_type, name
+        )));
+    }
+
+    /// Test if the shipped items are valid
+    #[test]
+    fn test_validate_shipped_item_data() {
+        let item_res =
+            from_bytes::<ItemResource>(include_bytes!("../assets/resources/item.ron")).unwrap();
+
+        let report = validate_item_data(&item_res);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    /// Test if missing items and broken modifiers are found
+    #[test]
+    fn test_validate_invalid_item_data() {
+        let mut item_res =
+            from_bytes::<ItemResource>(include_bytes!("../assets/resources/item.ron")).unwrap();
+        item_res.items.remove(&ITEM_TYPES[0]);
+        let item = item_res.items.get_mut(&ITEM_TYPES[1]).unwrap();
+        item.modifiers = vec![
+            StatModifier::Damage(0.0),
+            StatModifier::ShootingPattern(vec![]),
+        ];
+
+        let report = validate_item_data(&item_res);
+
+        assert_eq!(
+            report.errors,
+            vec![
+                format!("Item {:?} multiplies a weapon stat by 0", ITEM_TYPES[1]),
+                format!(
+                    "Item {:?} replaces the shooting pattern with an empty one",
+                    ITEM_TYPES[1]
+                ),
+                format!("Item {:?} is not defined", ITEM_TYPES[0]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+// --- Imports ---
+use bevy::prelude::*;
+use ron::{
+    de::from_bytes,
+    ser::{to_string_pretty, PrettyConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::{
+    controls::{Action, ActionState},
+    menu::{AppState, EndGameEvent},
+    spawnable::enemy::behavior::EnemySlainEvent,
+};
+
+// --- Plugin declaration ---
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_system_set(SystemSet::on_update(AppState::InGame).with_system(track_run_stats))
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(record_high_score))
+            .add_system_set(
+                SystemSet::on_enter(AppState::HighScores).with_system(spawn_high_scores),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::HighScores).with_system(high_scores_controls),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::HighScores).with_system(despawn_high_scores),
+            );
+    }
+}
+
+// --- Constants ---
+/// Number of runs which are kept in the high-score table
+pub const MAX_HIGH_SCORES: usize = 10;
+/// Path of the high-score table
+pub const HIGH_SCORE_PATH: &str = "saves/highscores.ron";
+
+// --- Resources ---
+/// Statistics of the current run which are not stored on the player
+///
+/// It is set when the player is spawned and saved with the run, so a continued run keeps its statistics.
+///
+/// * `duration` - Played time in seconds, the time in the pause menu isn't counted
+/// * `kills` - Number of slain enemies
+/// * `starting_weapon` - Name of the weapon the run was started with
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub duration: f32,
+    pub kills: u32,
+    pub starting_weapon: String,
+}
+
+/// Entry of the high-score table
+///
+/// * `score` - Score of the run
+/// * `boss_slain` - If the run was won
+/// * `floor` - Floor the run ended on
+/// * `seed` - Seed of the run
+/// * `duration` - Played time in seconds
+/// * `kills` - Number of slain enemies
+/// * `starting_weapon` - Name of the weapon the run was started with
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: i32,
+    pub boss_slain: bool,
+    pub floor: i32,
+    pub seed: u64,
+    pub duration: f32,
+    pub kills: u32,
+    pub starting_weapon: String,
+}
+
+/// Table of the best runs, sorted by score from highest to lowest
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    pub entries: Vec<HighScore>,
+}
+
+// --- Methods for Structs ---
+impl HighScore {
+    /// Create the high-score entry of a finished run
+    ///
+    /// # Arguments
+    /// * `game_end` - Event which ended the run
+    pub fn from_game_end(game_end: &EndGameEvent) -> Self {
+        HighScore {
+            score: game_end.score,
+            boss_slain: game_end.boss_slain,
+            floor: game_end.floor,
+            seed: game_end.seed,
+            duration: game_end.duration,
+            kills: game_end.kills,
+            starting_weapon: game_end.starting_weapon.clone(),
+        }
+    }
+}
+
+impl HighScoreTable {
+    /// Insert a run into the table, if it is good enough
+    ///
+    /// Runs with the same score keep their order, so an older run stays above a newer one.
+    ///
+    /// # Arguments
+    /// * `entry` - The run to insert
+    ///
+    /// # Returns
+    /// The rank of the run starting at 0, or None if it didn't make it into the table
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+// --- Components and Structs ---
+/// Marks the high-score screen and its camera for querying
+#[derive(Component)]
+struct HighScoreScreen {}
+
+// --- System-Functions ---
+/// Counts the played time and the slain enemies of the run
+fn track_run_stats(
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    mut ev_enemy_slain: EventReader<EnemySlainEvent>,
+) {
+    stats.duration += time.delta_seconds();
+    stats.kills += ev_enemy_slain.iter().count() as u32;
+}
+
+/// Adds a finished run to the high-score table
+fn record_high_score(mut ev_game_end: EventReader<EndGameEvent>) {
+    for game_end in ev_game_end.iter() {
+        let mut table = load_high_scores();
+        if let Some(rank) = table.insert(HighScore::from_game_end(game_end)) {
+            match write_high_scores(&table) {
+                Ok(()) => println!("New high score on rank {}", rank + 1),
+                Err(err) => println!("Error saving high scores: {}", err),
+            }
+        }
+    }
+}
+
+/// Returns to the main menu on back or confirm
+///
+/// The actions are reset, so the main menu doesn't react to them in the same frame.
+fn high_scores_controls(mut actions: ResMut<ActionState>, mut app_state: ResMut<State<AppState>>) {
+    if actions.any_just_pressed([Action::Back, Action::Confirm]) {
+        actions.reset_just_pressed();
+        app_state.set(AppState::MainMenu).unwrap();
+    }
+}
+
+/// Spawns the high-score screen
+fn spawn_high_scores(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(HighScoreScreen {});
+
+    let table = load_high_scores();
+
+    commands
+        .spawn_bundle(high_scores_wrapper())
+        .with_children(|parent| {
+            parent.spawn_bundle(header(&asset_server));
+            parent.spawn_bundle(high_scores_text(&asset_server, &table));
+            parent.spawn_bundle(back_text(&asset_server));
+        })
+        .insert(HighScoreScreen {});
+}
+
+/// Despawns the high-score screen and its camera when the main menu is opened
+fn despawn_high_scores(mut commands: Commands, query: Query<Entity, With<HighScoreScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// --- Functions ---
+/// Load the high-score table
+///
+/// # Returns
+/// The saved table, or an empty table if there is none or it is invalid
+pub fn load_high_scores() -> HighScoreTable {
+    let bytes = match fs::read(HIGH_SCORE_PATH) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                println!("Error reading high scores: {}", err);
+            }
+            return HighScoreTable::default();
+        }
+    };
+
+    from_bytes::<HighScoreTable>(&bytes).unwrap_or_else(|err| {
+        println!("High scores are invalid: {}", err);
+        HighScoreTable::default()
+    })
+}
+
+/// Write the high-score table to its file
+///
+/// # Arguments
+/// * `table` - The table to save
+fn write_high_scores(table: &HighScoreTable) -> io::Result<()> {
+    let text = to_string_pretty(table, PrettyConfig::default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    if let Some(directory) = Path::new(HIGH_SCORE_PATH).parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    fs::write(HIGH_SCORE_PATH, text)
+}
+
+/// Format a duration in seconds as minutes and seconds
+///
+/// # Arguments
+/// * `duration` - The duration in seconds
+fn format_duration(duration: f32) -> String {
+    let seconds = duration as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// --- Ui-Elements ---
+/// Wrapper for the high-score screen
+fn high_scores_wrapper() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: UiColor(Color::rgb(0.0, 0.0, 0.0)),
+        ..Default::default()
+    }
+}
+
+/// Header of the high-score screen
+fn header(asset_server: &Res<AssetServer>) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: "High Scores".to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 80.0,
+                    color: Color::rgb(1.0, 1.0, 1.0),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            margin: Rect {
+                bottom: Val::Px(40.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// One line for every run in the table
+fn high_scores_text(asset_server: &Res<AssetServer>, table: &HighScoreTable) -> TextBundle {
+    let text = if table.entries.is_empty() {
+        "No runs finished yet".to_string()
+    } else {
+        table
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                format!(
+                    "{}. {} - {} - Floor {} - {} - {} kills - {} - Seed {}",
+                    i + 1,
+                    entry.score,
+                    if entry.boss_slain { "Won" } else { "Lost" },
+                    entry.floor,
+                    format_duration(entry.duration),
+                    entry.kills,
+                    entry.starting_weapon,
+                    entry.seed
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: text,
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 24.0,
+                    color: Color::rgb(1.0, 1.0, 1.0),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Hint how to return to the main menu
+fn back_text(asset_server: &Res<AssetServer>) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: "Back".to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 40.0,
+                    color: Color::RED,
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            margin: Rect {
+                top: Val::Px(40.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Create a high-score entry with only a score
+    #[allow(dead_code)]
+    fn entry(score: i32) -> HighScore {
+        HighScore {
+            score,
+            boss_slain: false,
+            floor: 1,
+            seed: 0,
+            duration: 0.0,
+            kills: 0,
+            starting_weapon: String::new(),
+        }
+    }
+
+    /// Test if the table keeps only the best runs sorted by score
+    #[test]
+    fn test_insert_high_score() {
+        let mut table = HighScoreTable::default();
+        for score in 0..MAX_HIGH_SCORES as i32 {
+            table.insert(entry(score * 10));
+        }
+
+        assert_eq!(table.insert(entry(-5)), None);
+        assert_eq!(table.insert(entry(45)), Some(5));
+        assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(table.entries[0].score, 90);
+        assert_eq!(table.entries[MAX_HIGH_SCORES - 1].score, 10);
+    }
+}
@@ -1,6 +1,13 @@
 use crate::{
+    map::floor::{Floor, NextFloorEvent},
     menu::AppState,
-    spawnable::{behavior::Health, enemy::enemy_types::Boss, player::Player, weapon::WeaponList},
+    spawnable::{
+        behavior::Health,
+        enemy::{behavior::Enemy, enemy_types::Boss},
+        item::{ItemList, ItemResource},
+        player::{DodgeRoll, Player, DODGE_COOLDOWN},
+        weapon::WeaponList,
+    },
 };
 use bevy::prelude::*;
 
@@ -16,8 +23,11 @@ impl Plugin for HudPlugin {
             SystemSet::on_update(AppState::InGame)
                 .with_system(update_main_hud)
                 .with_system(show_boss_hud)
+                .with_system(hide_boss_hud)
                 .with_system(update_boss_hud)
-                .with_system(update_weapon_hud),
+                .with_system(update_weapon_hud)
+                .with_system(update_dodge_hud)
+                .with_system(update_item_hud),
         );
     }
 }
@@ -32,6 +42,10 @@ struct BossHealth {}
 #[derive(Component)]
 struct BossMaxHealth {}
 
+/// Marker for the health threshold of a boss phase
+#[derive(Component)]
+struct BossPhaseMarker {}
+
 /// The remain reload time for the player
 #[derive(Component)]
 struct ReloadBar {}
@@ -40,6 +54,10 @@ struct ReloadBar {}
 #[derive(Component)]
 struct AmmoBar {}
 
+/// The remaining cooldown of the dodge roll
+#[derive(Component)]
+struct DodgeBar {}
+
 /// The hud main text (health and score)
 #[derive(Component)]
 struct MainText {}
@@ -48,6 +66,14 @@ struct MainText {}
 #[derive(Component)]
 struct WeaponText {}
 
+/// The names of the collected items
+#[derive(Component)]
+struct ItemText {}
+
+// --- Type aliases ---
+/// Filter for the health bar of the boss and its background
+type BossBarFilter = Or<(With<BossHealth>, With<BossMaxHealth>)>;
+
 // --- System-Functions ---
 /// Spawns the HUD with all the necessary components
 fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -62,6 +88,9 @@ fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
             parent
                 .spawn_bundle(weapon_hud_text(&asset_server))
                 .insert(WeaponText {});
+            parent
+                .spawn_bundle(item_hud_text(&asset_server))
+                .insert(ItemText {});
             parent
                 .spawn_bundle(boss_bar_max())
                 .insert(BossMaxHealth {})
@@ -76,37 +105,77 @@ fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
             parent.spawn_bundle(ammo_bar_max()).with_children(|parent| {
                 parent.spawn_bundle(ammo_bar()).insert(AmmoBar {});
             });
+            parent
+                .spawn_bundle(dodge_bar_max())
+                .with_children(|parent| {
+                    parent.spawn_bundle(dodge_bar()).insert(DodgeBar {});
+                });
         });
 }
 
-/// Makes the Boss-Health-Bar visible if a Boss is spawned
+/// Makes the Boss-Health-Bar visible if a Boss is spawned and marks the thresholds of its phases
 fn show_boss_hud(
-    boss_spawn: Query<Entity, Added<Boss>>,
-    mut query_hud: Query<&mut Visibility, Or<(With<BossHealth>, With<BossMaxHealth>)>>,
-    // mut query_hud: Query<&mut Visibility, With<BossMaxHealth>>,
+    mut commands: Commands,
+    boss_spawn: Query<&Enemy, Added<Boss>>,
+    mut query_hud: Query<&mut Visibility, BossBarFilter>,
+    query_bar: Query<Entity, With<BossMaxHealth>>,
 ) {
-    for _boss_spawn in boss_spawn.iter() {
+    for boss in boss_spawn.iter() {
         for mut boss_hud in query_hud.iter_mut() {
             boss_hud.is_visible = true;
         }
+
+        // The first phase starts at full health and needs no marker
+        for phase in boss.phases.iter().skip(1) {
+            commands.entity(query_bar.single()).with_children(|parent| {
+                parent
+                    .spawn_bundle(boss_phase_marker(phase.health_threshold))
+                    .insert(BossPhaseMarker {});
+            });
+        }
+    }
+}
+
+/// Hides the Boss-Health-Bar and removes the phase markers if the player goes to the next floor
+fn hide_boss_hud(
+    mut commands: Commands,
+    mut ev_next_floor: EventReader<NextFloorEvent>,
+    mut query_hud: Query<&mut Visibility, BossBarFilter>,
+    query_marker: Query<Entity, With<BossPhaseMarker>>,
+) {
+    for _ev_next_floor in ev_next_floor.iter() {
+        for mut boss_hud in query_hud.iter_mut() {
+            boss_hud.is_visible = false;
+        }
+
+        for marker in query_marker.iter() {
+            commands.entity(marker).despawn();
+        }
     }
 }
 
-/// Updates the main HUD with the current health and score
+/// Updates the main HUD with the current health, score and floor
 fn update_main_hud(
+    floor: Res<Floor>,
     mut query_player: Query<(&Player, &Health, &WeaponList)>,
     mut query_main_text: Query<&mut Text, (With<MainText>, Without<WeaponText>)>,
     mut query_weapon_text: Query<&mut Text, (With<WeaponText>, Without<MainText>)>,
 ) {
     let (player, health, weaponlist) = query_player.single_mut();
-    let weapon = &weaponlist.weapons[0];
+    let weapon = weaponlist.active_weapon();
 
     let mut main_text = query_main_text.single_mut();
     main_text.sections[1].value = format!("{:?}", health.health);
     main_text.sections[3].value = format!("{:?}", player.score);
+    main_text.sections[5].value = format!("{:?}", floor.number);
 
     let mut weapon_text = query_weapon_text.single_mut();
-    weapon_text.sections[0].value = weapon.name.to_string();
+    weapon_text.sections[0].value = format!(
+        "{} ({}/{})",
+        weapon.name,
+        weaponlist.active + 1,
+        weaponlist.weapons.len()
+    );
 }
 
 // Updates the Boss-Health-Bar
@@ -135,7 +204,7 @@ fn update_weapon_hud(
     let mut ammo_bar_style = query_ammo_bar.single_mut();
 
     // Gets the current weapon
-    let weapon = &weaponlist.weapons[0];
+    let weapon = weaponlist.active_weapon();
 
     if weapon.reload_time.current > 0.0 {
         reload_bar_style.size = Size::new(
@@ -159,6 +228,38 @@ fn update_weapon_hud(
     }
 }
 
+// Updates the Dodge-Bar, it is full while the player is rolling
+fn update_dodge_hud(
+    query_player: Query<&DodgeRoll, With<Player>>,
+    mut query_dodge_bar: Query<&mut Style, With<DodgeBar>>,
+) {
+    let dodge_roll = query_player.single();
+    let mut dodge_bar_style = query_dodge_bar.single_mut();
+
+    dodge_bar_style.size = Size::new(
+        Val::Percent(100.0),
+        Val::Percent(dodge_roll.cooldown / DODGE_COOLDOWN * 100.0),
+    );
+}
+
+// Updates the list of the collected items
+fn update_item_hud(
+    item_res: Res<ItemResource>,
+    query_player: Query<&ItemList, With<Player>>,
+    mut query_item_text: Query<&mut Text, With<ItemText>>,
+) {
+    let item_list = query_player.single();
+    let mut item_text = query_item_text.single_mut();
+
+    item_text.sections[0].value = item_list
+        .items
+        .iter()
+        .filter_map(|item_type| item_res.items.get(item_type))
+        .map(|item| item.name.clone())
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
 // --- Ui-Elements ---
 
 /// Wrapper for the whole HUD
@@ -175,7 +276,7 @@ fn hud_wrapper() -> NodeBundle {
     }
 }
 
-/// Health and score of the player
+/// Health, score and floor of the player
 fn main_hud_text(asset_server: &Res<AssetServer>) -> TextBundle {
     TextBundle {
         text: Text {
@@ -196,6 +297,14 @@ fn main_hud_text(asset_server: &Res<AssetServer>) -> TextBundle {
                     value: "".to_string(),
                     style: main_hud_text_style(asset_server),
                 },
+                TextSection {
+                    value: "\nFloor: ".to_string(),
+                    style: main_hud_text_style(asset_server),
+                },
+                TextSection {
+                    value: "".to_string(),
+                    style: main_hud_text_style(asset_server),
+                },
             ],
             ..Default::default()
         },
@@ -244,6 +353,33 @@ fn weapon_hud_text(asset_server: &Res<AssetServer>) -> TextBundle {
     }
 }
 
+/// Item hud text
+fn item_hud_text(asset_server: &Res<AssetServer>) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: "".to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 25.0,
+                    color: Color::rgb(0.8, 0.8, 0.8),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                top: Val::Px(160.0),
+                left: Val::Px(5.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
 /// Full health bar for the boss
 fn boss_bar_max() -> NodeBundle {
     NodeBundle {
@@ -277,6 +413,26 @@ fn boss_bar() -> NodeBundle {
     }
 }
 
+/// Marker for the health threshold of a boss phase
+///
+/// # Arguments
+/// * `health_threshold` - Fraction of the maximum health at which the phase starts
+fn boss_phase_marker(health_threshold: f32) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(3.0), Val::Percent(100.0)),
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Percent(health_threshold * 100.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        color: UiColor(Color::rgb(0.0, 0.0, 0.0)),
+        ..Default::default()
+    }
+}
+
 /// Full reload time bar
 fn reload_bar_max() -> NodeBundle {
     NodeBundle {
@@ -341,6 +497,38 @@ fn ammo_bar() -> NodeBundle {
     }
 }
 
+/// Full dodge cooldown bar
+fn dodge_bar_max() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(15.0), Val::Px(100.0)),
+            position_type: PositionType::Absolute,
+            position: Rect {
+                bottom: Val::Px(50.0),
+                right: Val::Px(60.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        color: UiColor(Color::rgb(0.0, 0.4, 0.8)),
+        ..Default::default()
+    }
+}
+
+/// Remaining dodge cooldown bar
+fn dodge_bar() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(0.0)),
+            position_type: PositionType::Relative,
+            align_self: AlignSelf::FlexEnd,
+            ..Default::default()
+        },
+        color: UiColor(Color::rgb(0.0, 0.0, 0.2)),
+        ..Default::default()
+    }
+}
+
 /// Style for the main hud text
 fn main_hud_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
     TextStyle {
@@ -1,22 +1,38 @@
 // --- Plugins ---
 pub mod camera;
+pub mod controls;
 pub mod debug;
+pub mod game_data;
+pub mod highscore;
 pub mod hud;
 pub mod map;
 pub mod menu;
+pub mod pause;
+pub mod rng;
+pub mod save;
 pub mod spawnable;
 
 use camera::CameraPlugin;
+use controls::{ControlsPlugin, InputBindings};
 use debug::DebugPlugin;
+use game_data::{validate_game_data, validate_item_data, GameDataPlugin};
+use highscore::HighScorePlugin;
 use hud::HudPlugin;
-use map::MapPlugin;
+use map::{room_library::RoomLibrary, MapPlugin};
 use menu::{AppState, MenuPlugin};
+use pause::PausePlugin;
+use rng::{seed_from_args, GameRng};
+use save::SavePlugin;
 use spawnable::behavior::SpawnablePlugin;
 use spawnable::bullet::BulletPlugin;
+use spawnable::collision::CollisionMatrix;
 use spawnable::enemy::behavior::EnemyPlugin;
+use spawnable::enemy::boss::BossPlugin;
 use spawnable::enemy::enemy_types::EnemyResource;
+use spawnable::item::{ItemPlugin, ItemResource};
 use spawnable::movement::MovementPlugin;
 use spawnable::player::PlayerPlugin;
+use spawnable::status_effect::StatusEffectPlugin;
 use spawnable::weapon::{WeaponPlugin, WeaponResource};
 
 // --- Imports ---
@@ -42,34 +58,87 @@ impl Plugin for GamePlugin {
             .add_state(AppState::MainMenu)
             .add_plugin(BulletPlugin)
             .add_plugin(EnemyPlugin)
+            .add_plugin(BossPlugin)
             .add_plugin(DebugPlugin)
             .add_plugin(MapPlugin)
             .add_plugin(SpawnablePlugin)
             .add_plugin(HudPlugin)
             .add_plugin(WeaponPlugin)
+            .add_plugin(ItemPlugin)
             .add_plugin(CameraPlugin)
             .add_plugin(MovementPlugin)
-            .add_plugin(MenuPlugin);
+            .add_plugin(StatusEffectPlugin)
+            .add_plugin(MenuPlugin)
+            .add_plugin(PausePlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(HighScorePlugin)
+            .add_plugin(ControlsPlugin)
+            .add_plugin(GameDataPlugin)
+            .insert_resource(GameRng::new(seed_from_args()));
 
-        // Load files
+        // Load files, the enemies and weapons are reloaded from the asset folder by the game data plugin
         let enemy_resource =
             from_bytes::<EnemyResource>(include_bytes!("../assets/resources/enemy.ron"));
         let weapon_resource =
             from_bytes::<WeaponResource>(include_bytes!("../assets/resources/weapon.ron"));
+        let item_resource =
+            from_bytes::<ItemResource>(include_bytes!("../assets/resources/item.ron"));
+        let collision_matrix =
+            from_bytes::<CollisionMatrix>(include_bytes!("../assets/resources/collision.ron"));
+        let input_bindings =
+            from_bytes::<InputBindings>(include_bytes!("../assets/resources/bindings.ron"));
 
         // Checks if resource-files are corectly loaded
-        match (enemy_resource, weapon_resource) {
-            (Ok(enemy_resource), Ok(weapon_resource)) => {
+        match (
+            enemy_resource,
+            weapon_resource,
+            item_resource,
+            collision_matrix,
+            input_bindings,
+        ) {
+            (
+                Ok(enemy_resource),
+                Ok(weapon_resource),
+                Ok(item_resource),
+                Ok(collision_matrix),
+                Ok(input_bindings),
+            ) => {
+                // Checks if the enemies, weapons and rooms reference each other correctly
+                let report = validate_game_data(
+                    &enemy_resource,
+                    &weapon_resource,
+                    app.world.resource::<RoomLibrary>(),
+                );
+                report.print();
+                let item_report = validate_item_data(&item_resource);
+                item_report.print();
+                if !report.errors.is_empty() || !item_report.errors.is_empty() {
+                    println!("Error validating resource(s) - Ending program");
+                    exit(1);
+                }
+
                 app.insert_resource(enemy_resource);
                 app.insert_resource(weapon_resource);
+                app.insert_resource(item_resource);
+                app.insert_resource(collision_matrix);
+                app.insert_resource(input_bindings);
             }
-            (enemy_resource, weapon_resource) => {
+            (enemy_resource, weapon_resource, item_resource, collision_matrix, input_bindings) => {
                 if let Err(err) = enemy_resource {
                     println!("Error loading enemy resource: {}", err);
                 }
                 if let Err(err) = weapon_resource {
                     println!("Error loading weapon resource: {}", err);
                 }
+                if let Err(err) = item_resource {
+                    println!("Error loading item resource: {}", err);
+                }
+                if let Err(err) = collision_matrix {
+                    println!("Error loading collision matrix: {}", err);
+                }
+                if let Err(err) = input_bindings {
+                    println!("Error loading input bindings: {}", err);
+                }
                 println!("Error loading resource(s) - Ending program");
                 exit(1);
             }
@@ -0,0 +1,197 @@
+// --- Imports ---
+use bevy::prelude::*;
+use std::env;
+
+use crate::{
+    menu::AppState,
+    rng::GameRng,
+    save::DiscardRunEvent,
+    spawnable::{bullet::Bullet, collision::CollisionEvent, movement::Collider, player::Player},
+    PlayerCamera,
+};
+
+use super::{
+    calc_mid_room_pos, generate_layout, map_generation::RoomPos, room_library::RoomLibrary,
+    SpawnLayoutEvent, ROOM_HEIGHT, TILE_SIZE, X_MAP_LENGTH, Y_MAP_LENGTH,
+};
+
+// --- Plugin declaration ---
+pub struct FloorPlugin;
+
+impl Plugin for FloorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NextFloorEvent>()
+            .insert_resource(Floor::new(final_floor_from_args().unwrap_or(FINAL_FLOOR)))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(enter_trapdoor.after("step2").before("step3"))
+                    .with_system(next_floor.label("next_floor").after("step3")),
+            );
+    }
+}
+
+// --- Constants ---
+/// Floor after which the run ends when its boss is slain, if no other final floor is passed
+pub const FINAL_FLOOR: i32 = 3;
+
+// --- Resources ---
+/// Current floor of the run
+///
+/// * `number` - Number of the current floor, starting with 1
+/// * `final_floor` - Last floor of the run, the game is won when its boss is slain
+pub struct Floor {
+    pub number: i32,
+    pub final_floor: i32,
+}
+impl Floor {
+    /// Create the floor resource for the first floor
+    ///
+    /// # Arguments
+    /// * `final_floor` - Last floor of the run
+    pub fn new(final_floor: i32) -> Self {
+        Floor {
+            number: 1,
+            final_floor,
+        }
+    }
+
+    /// Check if the current floor is the last floor of the run
+    pub fn is_final(&self) -> bool {
+        self.number >= self.final_floor
+    }
+}
+
+// --- Components ---
+/// Trapdoor marker component
+///
+/// This marker is used to mark the trapdoor to the next floor
+#[derive(Component)]
+pub struct Trapdoor;
+
+// --- Events ---
+/// Event to signal that the player entered the trapdoor
+///
+/// This event is sent when the player walks into the trapdoor to generate the next floor
+pub struct NextFloorEvent;
+
+// --- Type aliases ---
+/// Filter for the rooms, enemies and bullets which are despawned when the floor is left
+type FloorEntityFilter = Or<(With<RoomPos>, With<Bullet>)>;
+
+// --- Functions ---
+/// Read the final floor from the command line arguments
+///
+/// The final floor is passed with `--final-floor <number>`
+///
+/// # Returns
+/// The final floor if one was passed and is a number of at least 1
+pub fn final_floor_from_args() -> Option<i32> {
+    let mut args = env::args().skip_while(|arg| arg != "--final-floor").skip(1);
+
+    match args.next().map(|floor| floor.parse::<i32>()) {
+        Some(Ok(floor)) if floor >= 1 => Some(floor),
+        Some(Ok(floor)) => {
+            println!("Invalid final floor {}, using floor {}", floor, FINAL_FLOOR);
+            None
+        }
+        Some(Err(err)) => {
+            println!("Invalid final floor, using floor {}: {}", FINAL_FLOOR, err);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Spawn the trapdoor to the next floor in the center of a room
+///
+/// # Arguments
+/// * `commands` - Commands to spawn the trapdoor
+/// * `room_pos` - Position of the room
+pub fn spawn_trapdoor(commands: &mut Commands, room_pos: RoomPos) {
+    let mid_pos = calc_mid_room_pos(room_pos.x, room_pos.y);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(mid_pos.x, mid_pos.y, ROOM_HEIGHT + 0.5),
+                scale: Vec3::new(TILE_SIZE, TILE_SIZE, 0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: Color::rgb(0.2, 0.1, 0.03),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(room_pos)
+        .insert(Trapdoor)
+        .insert(Name::new("Trapdoor"))
+        .insert(Collider::Trapdoor);
+}
+
+// --- Systems ---
+/// Sends the next floor event when the player collides with the trapdoor
+fn enter_trapdoor(
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_next_floor: EventWriter<NextFloorEvent>,
+) {
+    if ev_collision
+        .iter()
+        .any(|ev| ev.triggers(Collider::Trapdoor))
+    {
+        ev_next_floor.send(NextFloorEvent);
+    }
+}
+
+/// Move the player to the next floor
+///
+/// Despawns the current map and generates the layout of the next floor, which is spawned by the map plugin.
+/// The player keeps score, health and weapons and is moved with the camera to the start room of the new floor.
+/// If the floor can't be generated, the run is discarded and the game returns to the main menu.
+fn next_floor(
+    mut commands: Commands,
+    (mut ev_next_floor, mut ev_spawn_layout, mut ev_discard_run): (
+        EventReader<NextFloorEvent>,
+        EventWriter<SpawnLayoutEvent>,
+        EventWriter<DiscardRunEvent>,
+    ),
+    (mut floor, mut app_state): (ResMut<Floor>, ResMut<State<AppState>>),
+    (rng, room_library): (Res<GameRng>, Res<RoomLibrary>),
+    map_query: Query<Entity, FloorEntityFilter>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut camera_query: Query<&mut Transform, (With<PlayerCamera>, Without<Player>)>,
+) {
+    // The trapdoor can be entered multiple times in one frame, but only one floor is generated
+    if ev_next_floor.iter().count() == 0 {
+        return;
+    }
+
+    // Despawn the rooms, enemies and bullets of the current floor
+    for entity in map_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    floor.number += 1;
+    match generate_layout(rng.seed(), floor.number, &room_library) {
+        Ok(ev) => ev_spawn_layout.send(ev),
+        Err(err) => {
+            println!("Error generating floor {}: {}", floor.number, err);
+            ev_discard_run.send(DiscardRunEvent);
+            app_state.set(AppState::MainMenu).unwrap();
+            return;
+        }
+    }
+
+    let start_room_mid = calc_mid_room_pos(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
+
+    // Move the player and the camera to the start room
+    let mut player_transform = player_query.single_mut();
+    player_transform.translation = Vec3::new(
+        start_room_mid.x,
+        start_room_mid.y,
+        player_transform.translation.z,
+    );
+
+    let mut camera_transform = camera_query.single_mut();
+    camera_transform.translation = Vec3::new(start_room_mid.x, start_room_mid.y, 100.0);
+}
@@ -0,0 +1,155 @@
+// --- Imports ---
+use bevy::prelude::*;
+
+use crate::{
+    menu::AppState,
+    spawnable::{
+        behavior::Health,
+        collision::CollisionEvent,
+        movement::{check_collision, Collider},
+        player::{Invincibility, Player, DEFAULT_INVINCIBILITY_DURATION},
+        status_effect::{StatusEffect, StatusEffects, StatusKind},
+    },
+};
+
+use super::{calc_room_pos, map_generation::RoomPos, pathfinding::NavigationGrids, room::TileType};
+
+// --- Plugin declaration ---
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(tile_hazards.after("step3"))
+                .with_system(rock_hit.after("step2")),
+        );
+    }
+}
+
+// --- Constants ---
+/// Damage of spikes to the player
+pub const SPIKE_DAMAGE: f32 = 1.0;
+/// Number of player bullet hits until a rock breaks
+pub const ROCK_HITS: i32 = 4;
+/// Factor of the movement speed on sludge
+pub const SLUDGE_SPEED_FACTOR: f32 = 0.5;
+/// Seconds the slow of sludge lasts after leaving it
+const SLUDGE_DURATION: f32 = 0.1;
+
+// --- Components ---
+/// Rock component
+///
+/// The rock blocks walking and bullets and breaks after the remaining hits of the player
+#[derive(Component)]
+pub struct Rock {
+    pub hits: i32,
+}
+
+// --- System-Functions ---
+/// Applies the effects of the tiles the player and the walking enemies stand on
+///
+/// * `Spikes` - Damage the player, an invincible or rolling player isn't hurt
+/// * `Sludge` - Slows the player and the walking enemies
+fn tile_hazards(
+    mut commands: Commands,
+    tile_query: Query<(&TileType, &Transform, &RoomPos)>,
+    mut player_query: Query<
+        (&Transform, &mut Health, Option<&Invincibility>, Entity),
+        With<Player>,
+    >,
+    mut walker_query: Query<(&Transform, &Collider, &mut StatusEffects)>,
+) {
+    for (transform, mut health, invincibility, entity) in player_query.iter_mut() {
+        if invincibility.is_none()
+            && touched_tiles(transform, &tile_query).contains(&TileType::Spikes)
+        {
+            health.take_damage(SPIKE_DAMAGE);
+            commands.entity(entity).insert(Invincibility {
+                duration: DEFAULT_INVINCIBILITY_DURATION,
+            });
+        }
+    }
+
+    let sludge = StatusEffect {
+        kind: StatusKind::Slow,
+        duration: SLUDGE_DURATION,
+        damage: 0.0,
+        max_stacks: 1,
+        speed_factor: SLUDGE_SPEED_FACTOR,
+        fire_rate_factor: 1.0,
+    };
+
+    for (transform, collider, mut status_effects) in walker_query.iter_mut() {
+        // Flying enemies don't touch the ground
+        if *collider != Collider::FlyingEnemy
+            && touched_tiles(transform, &tile_query).contains(&TileType::Sludge)
+        {
+            status_effects.apply(&sludge);
+        }
+    }
+}
+
+/// Breaks a rock after it was hit often enough
+///
+/// The broken rock is removed like a destroyed secret wall and its tile can be walked on by the enemies
+fn rock_hit(
+    mut commands: Commands,
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut rock_query: Query<(&mut Rock, &Transform, &RoomPos)>,
+    mut navigation_grids: ResMut<NavigationGrids>,
+) {
+    for ev in ev_collision.iter() {
+        if !ev.triggers(Collider::Rock) {
+            continue;
+        }
+
+        if let Ok((mut rock, transform, room_pos)) = rock_query.get_mut(ev.other) {
+            rock.hits -= 1;
+
+            if rock.hits == 0 {
+                let mut entity = commands.entity(ev.other);
+                entity
+                    .remove::<Rock>()
+                    .remove::<Collider>()
+                    .remove::<Sprite>();
+                entity.insert(Sprite {
+                    color: Color::rgb(0.0, 0.0, 0.0),
+                    ..Default::default()
+                });
+
+                if let Some(grid) = navigation_grids.grids.get_mut(room_pos) {
+                    let tile = grid.world_to_tile(transform.translation.truncate());
+                    if let Some(tile_type) = grid
+                        .tiles
+                        .get_mut(tile.y as usize)
+                        .and_then(|row| row.get_mut(tile.x as usize))
+                    {
+                        *tile_type = TileType::Empty;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// --- Functions ---
+/// Get the types of the tiles an entity touches in its room
+///
+/// # Arguments
+/// * `transform` - The transform of the entity
+/// * `tile_query` - The tiles with a type
+fn touched_tiles(
+    transform: &Transform,
+    tile_query: &Query<(&TileType, &Transform, &RoomPos)>,
+) -> Vec<TileType> {
+    let room_pos = calc_room_pos(transform.translation.truncate());
+
+    tile_query
+        .iter()
+        .filter(|(_, tile_transform, tile_room_pos)| {
+            **tile_room_pos == room_pos && check_collision(transform, tile_transform)
+        })
+        .map(|(tile_type, _, _)| *tile_type)
+        .collect()
+}
@@ -1,33 +1,85 @@
 // --- Imports ---
-use super::{room::RoomType, room_generation::*, ROOM_DISTANCE, X_MAP_LENGTH, Y_MAP_LENGTH};
+use super::{
+    pathfinding::NavigationGrids,
+    room::{RoomStatus, RoomType},
+    room_generation::*,
+    room_library::RoomLibrary,
+    ROOM_DISTANCE, X_MAP_LENGTH, Y_MAP_LENGTH,
+};
+use crate::rng::GameRng;
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
 
 // --- Constants ---
 const MIN_ROOMS: usize = 8;
 const MAX_ROOMS: usize = 12;
 const ROOM_CHANCE: f32 = 0.4;
+/// Number of tries to generate a valid map
+const MAX_ATTEMPTS: u32 = 1000;
+/// Minimum number of neighbors of a secret room
+const SECRET_ROOM_MIN_NEIGHBORS: i32 = 2;
 
 // --- Structs ---
 /// Basic room information's to generate the map
-#[derive(Clone, Debug)]
+///
+/// `secret_neighbors` marks the neighbors which are secret rooms and only reachable through a secret wall
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RoomInformation {
     pub room_type: RoomType,
     pub position: RoomPos,
     pub neighbors: Neighbors,
+    pub secret_neighbors: Neighbors,
 }
 impl RoomInformation {
-    pub fn new(room_type: RoomType, x: i32, y: i32, neighbors: Neighbors) -> RoomInformation {
+    pub fn new(
+        room_type: RoomType,
+        x: i32,
+        y: i32,
+        neighbors: Neighbors,
+        secret_neighbors: Neighbors,
+    ) -> RoomInformation {
         RoomInformation {
             room_type,
             position: RoomPos { x, y },
             neighbors,
+            secret_neighbors,
+        }
+    }
+}
+
+/// Positions of the special rooms of a map
+///
+/// Every map has a boss room, the other special rooms are only added if there is a fitting position
+#[derive(Clone, Copy, Debug)]
+pub struct SpecialRooms {
+    pub boss: RoomPos,
+    pub treasure: Option<RoomPos>,
+    pub shop: Option<RoomPos>,
+    pub secret: Option<RoomPos>,
+}
+impl SpecialRooms {
+    /// Get the room type of a special room position
+    fn get_room_type(&self, room_pos: &RoomPos) -> Option<RoomType> {
+        if *room_pos == self.boss {
+            Some(RoomType::Boss)
+        } else if Some(*room_pos) == self.treasure {
+            Some(RoomType::Treasure)
+        } else if Some(*room_pos) == self.shop {
+            Some(RoomType::Shop)
+        } else if Some(*room_pos) == self.secret {
+            Some(RoomType::Secret)
+        } else {
+            None
         }
     }
 }
 
 /// Array based position of a room
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Component)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Component, Serialize, Deserialize,
+)]
 pub struct RoomPos {
     pub x: i32,
     pub y: i32,
@@ -55,7 +107,7 @@ impl RoomPos {
 }
 
 /// Neighbors of a room
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Neighbors {
     pub top: bool,
     pub bottom: bool,
@@ -91,30 +143,144 @@ impl Neighbors {
     }
 }
 
-/// Create a new map
-///
-/// Generate a new map with specific configurations and spawn it
-pub fn initialize_map(commands: &mut Commands) {
-    // Try room generations until a valid map is found
-    for _i in 0..1000 {
-        // Generate the positions of the rooms
-        let rooms = generate_map();
-        if rooms.len() >= MIN_ROOMS && rooms.len() <= MAX_ROOMS {
-            // Try to add an boss room to the map
-            let boss_room = get_boss_room(&rooms);
-            if let Some(boss_room) = boss_room {
+/// Configuration of the map generation
+///
+/// * `floor` - The number of the floor, which decides the room files
+/// * `min_rooms` - Minimum number of rooms without the secret room
+/// * `max_rooms` - Maximum number of rooms without the secret room
+/// * `room_chance` - Chance of a possible room to be added to the map
+/// * `max_attempts` - Number of tries to generate a map with a valid number of rooms and a boss room
+#[derive(Clone, Copy, Debug)]
+pub struct MapConfig {
+    pub floor: i32,
+    pub min_rooms: usize,
+    pub max_rooms: usize,
+    pub room_chance: f32,
+    pub max_attempts: u32,
+}
+impl MapConfig {
+    /// Get the default configuration for a floor
+    pub fn for_floor(floor: i32) -> MapConfig {
+        MapConfig {
+            floor,
+            min_rooms: MIN_ROOMS,
+            max_rooms: MAX_ROOMS,
+            room_chance: ROOM_CHANCE,
+            max_attempts: MAX_ATTEMPTS,
+        }
+    }
+}
+
+/// Error while generating or restoring a map
+///
+/// * `NoValidLayout` - No valid map was generated in the given number of attempts
+/// * `NoTemplate` - There is no room file for a room type on a floor
+/// * `UnknownTemplate` - A room file of a saved run isn't in the room library
+#[derive(Debug, PartialEq, Eq)]
+pub enum GenError {
+    NoValidLayout(u32),
+    NoTemplate(RoomType, i32),
+    UnknownTemplate(String),
+}
+
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenError::NoValidLayout(attempts) => {
+                write!(f, "No valid map was generated in {} attempts", attempts)
+            }
+            GenError::NoTemplate(room_type, floor) => {
+                write!(f, "There is no {:?} room for floor {}", room_type, floor)
+            }
+            GenError::UnknownTemplate(name) => write!(f, "There is no room file {}", name),
+        }
+    }
+}
+
+// --- Resources ---
+/// Layout of the current floor
+///
+/// Stores the generated map and the room template of every room, so the floor can be spawned, saved and spawned again
+///
+/// * `map` - The map as a two dimensional array
+/// * `templates` - The name of the room file of each room
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct MapLayout {
+    pub map: Vec<Vec<RoomInformation>>,
+    pub templates: HashMap<RoomPos, String>,
+}
+
+impl MapLayout {
+    /// Generate the layout of a floor
+    ///
+    /// The positions, types and neighbors of the rooms and their room files are chosen without spawning anything,
+    /// so the same seed, configuration and room files always generate the same layout.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the layout
+    /// * `config` - The configuration of the map generation
+    /// * `room_library` - The room files to choose from
+    ///
+    /// # Returns
+    /// The layout, or an error if no valid map was generated or a room file couldn't be chosen
+    pub fn generate(
+        seed: u64,
+        config: &MapConfig,
+        room_library: &RoomLibrary,
+    ) -> Result<MapLayout, GenError> {
+        let mut rng = GameRng::new(Some(seed));
+
+        // Try room generations until a valid map is found
+        for _i in 0..config.max_attempts {
+            // Generate the positions of the rooms
+            let mut rooms = generate_map(&mut rng, config.room_chance);
+            if rooms.len() < config.min_rooms || rooms.len() > config.max_rooms {
+                continue;
+            }
+
+            // Try to add an boss room and the other special rooms to the map
+            if let Some(special_rooms) = get_special_rooms(&mut rooms, &mut rng) {
                 // Convert the map from a one dimensional array to a two dimensional array and set some information's
-                let map = convert_rooms_to_map(&rooms, &boss_room);
-                // Spawn the map
-                spawn_map(commands, &map);
-                break;
+                let map = convert_rooms_to_map(&rooms, &special_rooms);
+
+                // Choose the room files in the order of the map, so the choice doesn't depend on the order of a hash map
+                let mut templates = HashMap::new();
+                for room in map.iter().flatten() {
+                    if room.room_type != RoomType::Empty {
+                        let template =
+                            room_library.choose(room.room_type, config.floor, &mut rng)?;
+                        templates.insert(room.position, template.to_string());
+                    }
+                }
+
+                return Ok(MapLayout { map, templates });
             }
         }
+
+        Err(GenError::NoValidLayout(config.max_attempts))
+    }
+
+    /// Check if the room files of every room are in the room library
+    ///
+    /// # Arguments
+    /// * `room_library` - The room files which can be spawned
+    ///
+    /// # Returns
+    /// An error for the first room file which isn't in the room library
+    pub fn check_templates(&self, room_library: &RoomLibrary) -> Result<(), GenError> {
+        for template in self.templates.values() {
+            room_library.get(template)?;
+        }
+        Ok(())
     }
 }
 
 /// Generate a one dimensional array of rooms
-fn generate_map() -> Vec<RoomPos> {
+///
+/// # Arguments
+/// * `rng` - The random number generator of the map
+/// * `room_chance` - Chance of a possible room to be added to the map
+fn generate_map(rng: &mut GameRng, room_chance: f32) -> Vec<RoomPos> {
     // Rooms that will be added to the map
     let mut rooms: Vec<RoomPos> = vec![];
     // Possible rooms which can be added to the map
@@ -133,7 +299,7 @@ fn generate_map() -> Vec<RoomPos> {
         // Get next possible room from queue
         let pos = queue.pop().unwrap();
 
-        check_possible_room(&pos, &mut rooms, &mut queue);
+        check_possible_room(&pos, &mut rooms, &mut queue, room_chance, rng);
     }
 
     rooms
@@ -147,10 +313,14 @@ fn generate_map() -> Vec<RoomPos> {
 /// * `room_pos` - Position of the room
 /// * `rooms` - Rooms that are already added to the map
 /// * `queue` - Possible rooms that could be added to the map but not checked yet
+/// * `room_chance` - Chance of the room to be added
+/// * `rng` - The random number generator of the map
 fn check_possible_room(
     room_pos: &RoomPos,
     rooms: &mut Vec<RoomPos>,
     queue: &mut Vec<RoomPos>,
+    room_chance: f32,
+    rng: &mut GameRng,
 ) -> bool {
     // Check if room has more than two neighbors
     if count_neighbors(room_pos, rooms) > 2 {
@@ -158,8 +328,7 @@ fn check_possible_room(
     }
 
     // Percent chance of adding room to map
-    let mut rng = rand::thread_rng();
-    if rng.gen::<f32>() < ROOM_CHANCE {
+    if rng.gen::<f32>() < room_chance {
         rooms.push(*room_pos);
         add_possible_neighbors(room_pos, rooms, queue);
     }
@@ -222,34 +391,126 @@ fn count_neighbors(room_pos: &RoomPos, rooms: &Vec<RoomPos>) -> i32 {
 
     count
 }
+/// Add the special rooms to the map
+///
+/// The boss, treasure and shop rooms are placed on different rooms with only one neighbor.
+/// The secret room is added on a free position with several neighbors.
+///
+/// # Arguments
+/// * `rooms` - The rooms of the map, the secret room will be added to them
+/// * `rng` - The random number generator of the map
+///
+/// # Returns
+/// The positions of the special rooms if a boss room was found
+fn get_special_rooms(rooms: &mut Vec<RoomPos>, rng: &mut GameRng) -> Option<SpecialRooms> {
+    let boss = get_boss_room(rooms, rng)?;
+    let treasure = get_dead_end_room(rooms, &[boss], rng);
+
+    let mut excluded = vec![boss];
+    excluded.extend(treasure);
+    let shop = get_dead_end_room(rooms, &excluded, rng);
+    excluded.extend(shop);
+
+    let secret = get_secret_room(rooms, &excluded, rng);
+    if let Some(secret) = secret {
+        rooms.push(secret);
+    }
+
+    Some(SpecialRooms {
+        boss,
+        treasure,
+        shop,
+        secret,
+    })
+}
+
 /// Add an boss room to the map
 ///
 /// Change one of a room with only one neighbor to an boss room
 ///
 /// # Arguments
 /// * `rooms` - The rooms of the map
+/// * `rng` - The random number generator of the map
 ///
 /// # Returns
 /// The position of the boss room if a room with only one neighbor was found
-fn get_boss_room(rooms: &Vec<RoomPos>) -> Option<RoomPos> {
+fn get_boss_room(rooms: &Vec<RoomPos>, rng: &mut GameRng) -> Option<RoomPos> {
+    get_dead_end_room(rooms, &[], rng)
+}
+
+/// Get a random room with only one neighbor
+///
+/// The start room and the excluded rooms are never chosen
+///
+/// # Arguments
+/// * `rooms` - The rooms of the map
+/// * `excluded` - Rooms which are already used
+/// * `rng` - The random number generator of the map
+///
+/// # Returns
+/// The position of the room if a room with only one neighbor was found
+fn get_dead_end_room(
+    rooms: &Vec<RoomPos>,
+    excluded: &[RoomPos],
+    rng: &mut GameRng,
+) -> Option<RoomPos> {
     // Get all rooms with only one neighbor
-    let mut boss_rooms: Vec<RoomPos> = vec![];
+    let mut dead_end_rooms: Vec<RoomPos> = vec![];
 
     for room in rooms {
-        count_neighbors(room, rooms);
         if count_neighbors(room, rooms) == 1
             && *room != RoomPos::new(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2)
+            && !excluded.contains(room)
         {
-            boss_rooms.push(*room);
+            dead_end_rooms.push(*room);
         }
     }
 
-    if boss_rooms.is_empty() {
+    if dead_end_rooms.is_empty() {
         None
     } else {
         // Get random room from the list
-        let mut rng = rand::thread_rng();
-        Some(boss_rooms[rng.gen_range(0..boss_rooms.len())])
+        Some(dead_end_rooms[rng.gen_range(0..dead_end_rooms.len())])
+    }
+}
+
+/// Get a random position for a secret room
+///
+/// The secret room is placed on a free position inside the map with several neighbors.
+/// It is never placed next to one of the excluded rooms, so their number of neighbors doesn't change.
+///
+/// # Arguments
+/// * `rooms` - The rooms of the map
+/// * `excluded` - Rooms which must not be next to the secret room
+/// * `rng` - The random number generator of the map
+///
+/// # Returns
+/// The position of the secret room if a free position with several neighbors was found
+fn get_secret_room(
+    rooms: &Vec<RoomPos>,
+    excluded: &[RoomPos],
+    rng: &mut GameRng,
+) -> Option<RoomPos> {
+    let mut secret_rooms: Vec<RoomPos> = vec![];
+
+    for y in 0..Y_MAP_LENGTH {
+        for x in 0..X_MAP_LENGTH {
+            let pos = RoomPos::new(x, y);
+
+            if !rooms.contains(&pos)
+                && count_neighbors(&pos, rooms) >= SECRET_ROOM_MIN_NEIGHBORS
+                && count_neighbors(&pos, &excluded.to_vec()) == 0
+            {
+                secret_rooms.push(pos);
+            }
+        }
+    }
+
+    if secret_rooms.is_empty() {
+        None
+    } else {
+        // Get random position from the list
+        Some(secret_rooms[rng.gen_range(0..secret_rooms.len())])
     }
 }
 
@@ -257,11 +518,16 @@ fn get_boss_room(rooms: &Vec<RoomPos>) -> Option<RoomPos> {
 ///
 /// # Arguments
 /// * `rooms` - The rooms of the map
-/// * `boss_room` - The position of the boss room
+/// * `special_rooms` - The positions of the special rooms
 ///
 /// # Returns
 /// The map as a two dimensional array
-fn convert_rooms_to_map(rooms: &Vec<RoomPos>, boss_room: &RoomPos) -> Vec<Vec<RoomInformation>> {
+fn convert_rooms_to_map(
+    rooms: &Vec<RoomPos>,
+    special_rooms: &SpecialRooms,
+) -> Vec<Vec<RoomInformation>> {
+    let secret_rooms: Vec<RoomPos> = special_rooms.secret.into_iter().collect();
+
     // Initialize two dimensional array map with rooms
     let mut map: Vec<Vec<RoomInformation>> = vec![];
 
@@ -269,21 +535,30 @@ fn convert_rooms_to_map(rooms: &Vec<RoomPos>, boss_room: &RoomPos) -> Vec<Vec<Ro
     for y in 0..Y_MAP_LENGTH {
         let mut row: Vec<RoomInformation> = vec![];
         for x in 0..X_MAP_LENGTH {
+            let pos = RoomPos::new(x, y);
+
             // Calculate the neighbors of the room for further use
-            let neighbors = Neighbors::new_with_rooms(&RoomPos::new(x, y), rooms);
+            let neighbors = Neighbors::new_with_rooms(&pos, rooms);
+            let secret_neighbors = Neighbors::new_with_rooms(&pos, &secret_rooms);
 
             // Add room type, position and neighbors to the room
-            if rooms.contains(&RoomPos::new(x, y)) {
-                if y == Y_MAP_LENGTH / 2 && x == X_MAP_LENGTH / 2 {
-                    row.push(RoomInformation::new(RoomType::Start, x, y, neighbors));
-                } else if y == boss_room.y && x == boss_room.x {
-                    row.push(RoomInformation::new(RoomType::Boss, x, y, neighbors));
-                } else {
-                    row.push(RoomInformation::new(RoomType::Normal, x, y, neighbors));
-                }
+            let room_type = if !rooms.contains(&pos) {
+                RoomType::Empty
+            } else if y == Y_MAP_LENGTH / 2 && x == X_MAP_LENGTH / 2 {
+                RoomType::Start
+            } else if let Some(room_type) = special_rooms.get_room_type(&pos) {
+                room_type
             } else {
-                row.push(RoomInformation::new(RoomType::Empty, x, y, neighbors));
-            }
+                RoomType::Normal
+            };
+
+            row.push(RoomInformation::new(
+                room_type,
+                x,
+                y,
+                neighbors,
+                secret_neighbors,
+            ));
         }
         map.push(row);
     }
@@ -291,25 +566,121 @@ fn convert_rooms_to_map(rooms: &Vec<RoomPos>, boss_room: &RoomPos) -> Vec<Vec<Ro
     map
 }
 
-/// Spawn map by spawning every room in the map
+/// Spawn map by spawning every room in the layout
+///
+/// The navigation grids of the rooms are stored in the `NavigationGrids` resource
+/// and the layout of the map in the `MapLayout` resource
 ///
 /// # Arguments
-/// * `map` - The map as a two dimensional array
-fn spawn_map(commands: &mut Commands, map: &Vec<Vec<RoomInformation>>) {
-    for row in map {
+/// * `commands` - The commands to spawn the map with
+/// * `layout` - The generated layout of the floor
+/// * `room_library` - The room files of the layout
+/// * `floor` - The number of the floor
+/// * `rng` - The random number generator of the run
+///
+/// # Returns
+/// An error if a room file of the layout isn't in the room library
+pub fn spawn_map(
+    commands: &mut Commands,
+    layout: &MapLayout,
+    room_library: &RoomLibrary,
+    floor: i32,
+    rng: &mut GameRng,
+) -> Result<(), GenError> {
+    // Check the room files first, so no room is spawned for an invalid layout
+    layout.check_templates(room_library)?;
+    let mut navigation_grids = NavigationGrids::default();
+
+    for row in layout.map.iter() {
+        for room in row {
+            if let Some(template) = layout.templates.get(&room.position) {
+                let navigation_grid = create_room(
+                    commands,
+                    room,
+                    calc_room_offset(&room.position),
+                    room_library.get(template)?,
+                    floor,
+                    rng,
+                );
+                navigation_grids
+                    .grids
+                    .insert(room.position, navigation_grid);
+            }
+        }
+    }
+
+    // Replace the navigation grids and the layout of the previous floor
+    commands.insert_resource(navigation_grids);
+    commands.insert_resource(layout.clone());
+    Ok(())
+}
+
+/// Spawn the map of a saved floor again
+///
+/// The rooms are built from their saved templates and keep their saved status.
+/// Their spawners aren't spawned, because the remaining spawners are saved separately.
+///
+/// # Arguments
+/// * `commands` - The commands to spawn the map with
+/// * `room_library` - The room files of the saved templates
+/// * `layout` - The saved layout of the floor
+/// * `room_statuses` - The saved status of every room
+///
+/// # Returns
+/// An error if a saved room file isn't in the room library
+pub fn restore_map(
+    commands: &mut Commands,
+    room_library: &RoomLibrary,
+    layout: &MapLayout,
+    room_statuses: &HashMap<RoomPos, RoomStatus>,
+) -> Result<(), GenError> {
+    let mut navigation_grids = NavigationGrids::default();
+
+    // A secret room which isn't closed anymore was found by destroying its secret wall
+    let is_found = |room_pos: RoomPos| matches!(room_statuses.get(&room_pos), Some(status) if *status != RoomStatus::Closed);
+
+    for row in layout.map.iter() {
         for room in row {
-            if room.room_type != RoomType::Empty {
-                create_room(
+            if let Some(template) = layout.templates.get(&room.position) {
+                let found_secret_neighbors = Neighbors {
+                    top: room.secret_neighbors.top && is_found(room.position.up()),
+                    bottom: room.secret_neighbors.bottom && is_found(room.position.down()),
+                    left: room.secret_neighbors.left && is_found(room.position.left()),
+                    right: room.secret_neighbors.right && is_found(room.position.right()),
+                };
+
+                let navigation_grid = restore_room(
                     commands,
                     room,
-                    Vec2::new(
-                        room.position.x as f32 * ROOM_DISTANCE,
-                        room.position.y as f32 * ROOM_DISTANCE,
-                    ),
+                    calc_room_offset(&room.position),
+                    room_library.get(template)?,
+                    room_statuses
+                        .get(&room.position)
+                        .cloned()
+                        .unwrap_or(RoomStatus::Closed),
+                    found_secret_neighbors,
                 );
+                navigation_grids
+                    .grids
+                    .insert(room.position, navigation_grid);
             }
         }
     }
+
+    commands.insert_resource(navigation_grids);
+    commands.insert_resource(layout.clone());
+    Ok(())
+}
+
+/// Calculate the offset of the first tile of a room
+///
+/// # Arguments
+/// * `room_pos` - The position of the room
+fn calc_room_offset(room_pos: &RoomPos) -> Vec2 {
+    Vec2::new(
+        room_pos.x as f32 * ROOM_DISTANCE,
+        room_pos.y as f32 * ROOM_DISTANCE,
+    )
 }
 
 /// Add two positions together
@@ -359,14 +730,15 @@ mod tests {
         let mut count = 0;
         let mut failed = 0;
         let mut boss_room_fails = 0;
+        let mut rng = GameRng::new(None);
 
         // Try room generations until a valid map is found
         for _i in 0..trials {
-            let rooms = generate_map();
+            let rooms = generate_map(&mut rng, ROOM_CHANCE);
             if rooms.len() >= MIN_ROOMS && rooms.len() <= MAX_ROOMS {
                 count += rooms.len();
 
-                let boss_room = get_boss_room(&rooms);
+                let boss_room = get_boss_room(&rooms, &mut rng);
                 if boss_room.is_none() {
                     boss_room_fails += 1;
                     failed += 1;
@@ -395,20 +767,74 @@ mod tests {
         );
     }
 
+    /// Test if the same seed generates the same map
+    #[test]
+    fn test_seeded_map_generation() {
+        let mut first_rng = GameRng::new(Some(42));
+        let mut second_rng = GameRng::new(Some(42));
+
+        for _i in 0..100 {
+            assert_eq!(
+                generate_map(&mut first_rng, ROOM_CHANCE),
+                generate_map(&mut second_rng, ROOM_CHANCE)
+            );
+        }
+    }
+
+    /// Test if the special rooms are placed on valid positions
+    #[test]
+    fn test_special_rooms() {
+        let mut rng = GameRng::new(Some(7));
+        let start_room = RoomPos::new(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
+
+        for _i in 0..1000 {
+            let mut rooms = generate_map(&mut rng, ROOM_CHANCE);
+            let initial_rooms = rooms.clone();
+
+            if let Some(special_rooms) = get_special_rooms(&mut rooms, &mut rng) {
+                // Boss, treasure and shop are different dead ends
+                let dead_ends: Vec<RoomPos> = [
+                    Some(special_rooms.boss),
+                    special_rooms.treasure,
+                    special_rooms.shop,
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+
+                for (i, room) in dead_ends.iter().enumerate() {
+                    assert_ne!(*room, start_room);
+                    assert_eq!(count_neighbors(room, &rooms), 1);
+                    assert!(!dead_ends[i + 1..].contains(room));
+                }
+
+                // The secret room is a new room with several neighbors
+                if let Some(secret) = special_rooms.secret {
+                    assert!(!initial_rooms.contains(&secret));
+                    assert!(count_neighbors(&secret, &initial_rooms) >= SECRET_ROOM_MIN_NEIGHBORS);
+                    assert_eq!(rooms.len(), initial_rooms.len() + 1);
+                } else {
+                    assert_eq!(rooms.len(), initial_rooms.len());
+                }
+            }
+        }
+    }
+
     /// Test if the map is generated correctly
     #[test]
     fn test_map_generation() {
+        let mut rng = GameRng::new(None);
         for _i in 0..1000 {
             // Generate the positions of the rooms
-            let rooms = generate_map();
+            let mut rooms = generate_map(&mut rng, ROOM_CHANCE);
             if rooms.len() >= MIN_ROOMS && rooms.len() <= MAX_ROOMS {
-                let initial_room_count = rooms.len();
+                // Try to add an boss room and the other special rooms to the map
+                let special_rooms = get_special_rooms(&mut rooms, &mut rng);
+                if let Some(special_rooms) = special_rooms {
+                    let initial_room_count = rooms.len();
 
-                // Try to add an boss room to the map
-                let boss_room = get_boss_room(&rooms);
-                if let Some(boss_room) = boss_room {
                     // Convert the map from a one dimensional array to a two dimensional array and set some information's
-                    let map = convert_rooms_to_map(&rooms, &boss_room);
+                    let map = convert_rooms_to_map(&rooms, &special_rooms);
 
                     let mut room_count = 0;
                     // Add rooms to map
@@ -423,6 +849,15 @@ mod tests {
                             } else if room.room_type == RoomType::Boss {
                                 print!("{0: <3} |", "B");
                                 room_count += 1;
+                            } else if room.room_type == RoomType::Treasure {
+                                print!("{0: <3} |", "T");
+                                room_count += 1;
+                            } else if room.room_type == RoomType::Shop {
+                                print!("{0: <3} |", "$");
+                                room_count += 1;
+                            } else if room.room_type == RoomType::Secret {
+                                print!("{0: <3} |", "?");
+                                room_count += 1;
                             } else {
                                 print!("{0: <3} |", "#");
                             }
@@ -438,4 +873,97 @@ mod tests {
 
         println!("Map generation test failed");
     }
+
+    /// Load the shipped room files
+    #[allow(dead_code)]
+    fn load_room_library() -> RoomLibrary {
+        RoomLibrary::load(std::path::Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    /// Test if the generated layouts are connected, have a valid number of rooms and a boss room at a dead end
+    #[test]
+    fn test_layout_properties() {
+        let room_library = load_room_library();
+        let config = MapConfig::for_floor(1);
+
+        for seed in 0..500 {
+            let layout = MapLayout::generate(seed, &config, &room_library).unwrap();
+            let room_at = |pos: RoomPos| &layout.map[pos.y as usize][pos.x as usize];
+            let rooms: Vec<&RoomInformation> = layout
+                .map
+                .iter()
+                .flatten()
+                .filter(|room| room.room_type != RoomType::Empty)
+                .collect();
+
+            // Room count bounds, the secret room is added to the valid map
+            let count = rooms
+                .iter()
+                .filter(|room| room.room_type != RoomType::Secret)
+                .count();
+            assert!(count >= config.min_rooms && count <= config.max_rooms);
+
+            // Every room has a room file of its type
+            assert_eq!(layout.templates.len(), rooms.len());
+            for room in rooms.iter() {
+                let template = &layout.templates[&room.position];
+                assert!(room_library.groups[&room.room_type].contains(template));
+            }
+
+            // Connectivity, every room is reachable from the start room through its doors
+            let start = RoomPos::new(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
+            assert_eq!(room_at(start).room_type, RoomType::Start);
+            let mut reached = vec![start];
+            let mut open = vec![start];
+            while let Some(pos) = open.pop() {
+                let neighbors = room_at(pos).neighbors;
+                for (has_neighbor, next) in [
+                    (neighbors.top, pos.up()),
+                    (neighbors.bottom, pos.down()),
+                    (neighbors.left, pos.left()),
+                    (neighbors.right, pos.right()),
+                ] {
+                    if has_neighbor && !reached.contains(&next) {
+                        assert_ne!(room_at(next).room_type, RoomType::Empty);
+                        reached.push(next);
+                        open.push(next);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), rooms.len());
+
+            // The boss room is a dead end other than the start room, so the secret room isn't next to it.
+            // Dead ends next to the start room can become the boss room.
+            let bosses: Vec<&&RoomInformation> = rooms
+                .iter()
+                .filter(|room| room.room_type == RoomType::Boss)
+                .collect();
+            assert_eq!(bosses.len(), 1);
+            let boss = bosses[0];
+            let all_rooms: Vec<RoomPos> = rooms.iter().map(|room| room.position).collect();
+            assert_eq!(count_neighbors(&boss.position, &all_rooms), 1);
+            assert_ne!(boss.position, start);
+        }
+    }
+
+    /// Test if the same seed generates the same layout and an impossible configuration returns an error
+    #[test]
+    fn test_layout_generation_result() {
+        let room_library = load_room_library();
+        let config = MapConfig::for_floor(2);
+
+        let first = MapLayout::generate(42, &config, &room_library).unwrap();
+        let second = MapLayout::generate(42, &config, &room_library).unwrap();
+        assert_eq!(first.templates, second.templates);
+
+        let config = MapConfig {
+            min_rooms: (X_MAP_LENGTH * Y_MAP_LENGTH) as usize + 1,
+            max_attempts: 10,
+            ..config
+        };
+        assert_eq!(
+            MapLayout::generate(42, &config, &room_library).unwrap_err(),
+            GenError::NoValidLayout(10)
+        );
+    }
 }
@@ -1,23 +1,62 @@
 // --- Imports ---
-use crate::menu::AppState;
+use crate::{
+    menu::AppState,
+    rng::GameRng,
+    save::{restore_pickups, DiscardRunEvent, RunSave},
+    spawnable::{item::ItemResource, weapon::WeaponResource},
+};
 use bevy::prelude::*;
+use map_generation::RoomPos;
+use rand::{distributions::Standard, Rng};
+use std::process::exit;
 
 // --- Plugins imports ---
+pub mod floor;
+pub mod hazard;
 pub mod map_generation;
+pub mod pathfinding;
 pub mod room;
 pub mod room_generation;
+pub mod room_library;
+pub mod shop;
 
-use self::map_generation::{initialize_map, RoomPos};
-use self::room::{RoomFinishedEvent, RoomPlugin};
+use self::floor::{spawn_trapdoor, Floor, FloorPlugin};
+use self::hazard::HazardPlugin;
+use self::map_generation::{restore_map, spawn_map, GenError, MapConfig, MapLayout};
+use self::pathfinding::NavigationGrids;
+use self::room::{RoomPlugin, RoomStatus, RoomType};
+use self::room_generation::spawn_spawner;
+use self::room_library::RoomLibrary;
+use self::shop::ShopPlugin;
 
 // --- Plugin declaration ---
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(RoomPlugin).add_system_set(
-            SystemSet::on_enter(AppState::InGame).with_system(start_map_generation),
-        );
+        app.add_plugin(RoomPlugin)
+            .add_plugin(FloorPlugin)
+            .add_plugin(HazardPlugin)
+            .add_plugin(ShopPlugin)
+            .init_resource::<NavigationGrids>()
+            .init_resource::<MapLayout>()
+            .add_event::<SpawnLayoutEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(start_map_generation))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(spawn_layout.after("next_floor")),
+            );
+
+        // The room files are only read once, the rooms of every floor are chosen from the library
+        match RoomLibrary::load_assets() {
+            Ok(room_library) => {
+                app.insert_resource(room_library);
+            }
+            Err(err) => {
+                println!("Error loading room library: {} - Ending program", err);
+                exit(1);
+            }
+        }
     }
 }
 
@@ -38,23 +77,135 @@ pub const ROOM_HEIGHT: f32 = -1.0;
 /// The size of a tile
 pub const TILE_SIZE: f32 = 60.0;
 
+// --- Events ---
+/// Event to spawn a generated layout
+///
+/// * `layout` - The layout of the floor
+/// * `floor` - The number of the floor
+/// * `spawner_seed` - The seed which chooses the enemies of the spawners
+pub struct SpawnLayoutEvent {
+    pub layout: MapLayout,
+    pub floor: i32,
+    pub spawner_seed: u64,
+}
+
 // --- Systems ---
 /// Start the map generation
 ///
-/// This system is called when the player enters the game state and will initialize the map
+/// This system is called when the player enters the game state and will initialize the map of the first floor.
+/// If a saved run is continued, the saved floor with its remaining spawners and pickups is spawned instead.
+/// If the map can't be generated, the run is discarded and the game returns to the main menu.
 fn start_map_generation(
     mut commands: Commands,
-    mut ev_room_finished: EventWriter<RoomFinishedEvent>,
+    mut floor: ResMut<Floor>,
+    (rng, room_library): (Res<GameRng>, Res<RoomLibrary>),
+    (item_res, weapon_res): (Res<ItemResource>, Res<WeaponResource>),
+    mut app_state: ResMut<State<AppState>>,
+    (mut ev_spawn_layout, mut ev_discard_run): (
+        EventWriter<SpawnLayoutEvent>,
+        EventWriter<DiscardRunEvent>,
+    ),
+    resume: Option<Res<RunSave>>,
 ) {
-    initialize_map(&mut commands);
+    if let Some(save) = resume {
+        floor.number = save.floor;
+        if let Err(err) = restore_map(&mut commands, &room_library, &save.layout, &save.rooms) {
+            println!("Error restoring the map: {}", err);
+            ev_discard_run.send(DiscardRunEvent);
+            app_state.set(AppState::MainMenu).unwrap();
+            return;
+        }
+
+        for spawner in save.spawners.iter() {
+            spawn_spawner(
+                &mut commands,
+                spawner.position,
+                spawner.room_pos,
+                spawner.enemy_type.clone(),
+                spawner.state.clone(),
+            );
+        }
+        restore_pickups(&mut commands, &save.pickups, &item_res, &weapon_res);
+
+        // The trapdoor is opened when the boss of the floor was slain
+        for row in save.layout.map.iter() {
+            for room in row {
+                if room.room_type == RoomType::Boss
+                    && save.rooms.get(&room.position) == Some(&RoomStatus::Finished)
+                    && !floor.is_final()
+                {
+                    spawn_trapdoor(&mut commands, room.position);
+                }
+            }
+        }
+        return;
+    }
+
+    floor.number = 1;
+    match generate_layout(rng.seed(), floor.number, &room_library) {
+        Ok(ev) => ev_spawn_layout.send(ev),
+        Err(err) => {
+            println!("Error generating the map: {}", err);
+            ev_discard_run.send(DiscardRunEvent);
+            app_state.set(AppState::MainMenu).unwrap();
+        }
+    }
+}
 
-    ev_room_finished.send(RoomFinishedEvent(RoomPos::new(
-        X_MAP_LENGTH / 2,
-        Y_MAP_LENGTH / 2,
-    )));
+/// Spawns the rooms of a generated layout
+///
+/// If a room file of the layout is missing, the run is discarded and the game returns to the main menu.
+fn spawn_layout(
+    mut commands: Commands,
+    (mut ev_spawn_layout, mut ev_discard_run): (
+        EventReader<SpawnLayoutEvent>,
+        EventWriter<DiscardRunEvent>,
+    ),
+    room_library: Res<RoomLibrary>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    for ev in ev_spawn_layout.iter() {
+        let mut rng = GameRng::new(Some(ev.spawner_seed));
+        if let Err(err) = spawn_map(&mut commands, &ev.layout, &room_library, ev.floor, &mut rng) {
+            println!("Error spawning the map: {}", err);
+            ev_discard_run.send(DiscardRunEvent);
+            app_state.set(AppState::MainMenu).unwrap();
+        }
+    }
 }
 
 // --- Functions ---
+/// Generate the layout of a floor
+///
+/// The seeds of a floor only depend on the seed of the run and the number of the floor,
+/// so the other random decisions of the run, like the starting weapon, don't change the floor.
+///
+/// # Arguments
+/// * `run_seed` - The seed of the run
+/// * `floor` - The number of the floor
+/// * `room_library` - The room files to choose from
+///
+/// # Returns
+/// The event to spawn the layout, or an error if no valid layout was generated
+pub fn generate_layout(
+    run_seed: u64,
+    floor: i32,
+    room_library: &RoomLibrary,
+) -> Result<SpawnLayoutEvent, GenError> {
+    // The n-th number of the run seed is the seed of the n-th floor
+    let floor_seed = GameRng::new(Some(run_seed))
+        .sample_iter(Standard)
+        .nth(floor.max(1) as usize - 1)
+        .unwrap_or(run_seed);
+    let mut floor_rng = GameRng::new(Some(floor_seed));
+
+    Ok(SpawnLayoutEvent {
+        layout: MapLayout::generate(floor_rng.gen(), &MapConfig::for_floor(floor), room_library)?,
+        floor,
+        spawner_seed: floor_rng.gen(),
+    })
+}
+
 /// Calculate the center position of a specified room position
 ///
 /// # Arguments
@@ -69,3 +220,32 @@ pub fn calc_mid_room_pos(x: i32, y: i32) -> Vec2 {
         y as f32 * ROOM_DISTANCE + Y_ROOM_LENGTH as f32 * TILE_SIZE / 2.0 - TILE_SIZE / 2.0,
     )
 }
+
+/// Calculate the position of the room which contains a world position
+///
+/// # Arguments
+/// * `position` - The world position
+///
+/// # Returns
+/// The position of the nearest room
+pub fn calc_room_pos(position: Vec2) -> RoomPos {
+    let first_room_mid = calc_mid_room_pos(0, 0);
+
+    RoomPos::new(
+        ((position.x - first_room_mid.x) / ROOM_DISTANCE).round() as i32,
+        ((position.y - first_room_mid.y) / ROOM_DISTANCE).round() as i32,
+    )
+}
+
+/// Check if a world position lies inside of a room, including its walls and transitions
+///
+/// # Arguments
+/// * `room_pos` - The position of the room
+/// * `position` - The world position
+pub fn is_in_room(room_pos: RoomPos, position: Vec2) -> bool {
+    let mid_pos = calc_mid_room_pos(room_pos.x, room_pos.y);
+    let distance = (position - mid_pos).abs();
+
+    distance.x <= (X_ROOM_LENGTH as f32 / 2.0 + 2.0) * TILE_SIZE
+        && distance.y <= (Y_ROOM_LENGTH as f32 / 2.0 + 2.0) * TILE_SIZE
+}
@@ -0,0 +1,347 @@
+// --- Imports ---
+use super::{map_generation::RoomPos, room::TileType, TILE_SIZE};
+use bevy::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+// --- Constants ---
+/// Cost of a straight step between two tiles
+const STRAIGHT_COST: u32 = 10;
+/// Cost of a diagonal step between two tiles
+const DIAGONAL_COST: u32 = 14;
+
+// --- Structs ---
+/// Position of a tile inside a room
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TilePos {
+    pub x: i32,
+    pub y: i32,
+}
+impl TilePos {
+    pub fn new(x: i32, y: i32) -> TilePos {
+        TilePos { x, y }
+    }
+}
+
+/// Tiles of a room which are used to find paths through the room
+#[derive(Clone, Debug)]
+pub struct NavigationGrid {
+    pub tiles: Vec<Vec<TileType>>,
+    pub offset: Vec2,
+}
+impl NavigationGrid {
+    /// Create a navigation grid
+    ///
+    /// # Arguments
+    /// * `tiles` - Tiles of the room, indexed by `[y][x]`
+    /// * `offset` - World position of the bottom left tile
+    pub fn new(tiles: Vec<Vec<TileType>>, offset: Vec2) -> NavigationGrid {
+        NavigationGrid { tiles, offset }
+    }
+
+    /// Check if a tile can be walked on, the floor hazards are walkable
+    pub fn is_walkable(&self, tile: TilePos) -> bool {
+        if tile.x < 0 || tile.y < 0 {
+            return false;
+        }
+
+        matches!(
+            self.tiles
+                .get(tile.y as usize)
+                .and_then(|row| row.get(tile.x as usize)),
+            Some(TileType::Empty | TileType::Spawner | TileType::Spikes | TileType::Sludge)
+        )
+    }
+
+    /// Get all tiles which can be walked on
+    pub fn walkable_tiles(&self) -> Vec<TilePos> {
+        let mut tiles = vec![];
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for x in 0..row.len() {
+                let tile = TilePos::new(x as i32, y as i32);
+                if self.is_walkable(tile) {
+                    tiles.push(tile);
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// Convert a world position to the tile at this position
+    pub fn world_to_tile(&self, position: Vec2) -> TilePos {
+        let relative = (position - self.offset) / TILE_SIZE;
+        TilePos::new(
+            (relative.x + 0.5).floor() as i32,
+            (relative.y + 0.5).floor() as i32,
+        )
+    }
+
+    /// Convert a tile to the world position of its center
+    pub fn tile_to_world(&self, tile: TilePos) -> Vec2 {
+        self.offset + Vec2::new(tile.x as f32, tile.y as f32) * TILE_SIZE
+    }
+}
+
+// --- Resources ---
+/// Navigation grids of all rooms of the current map
+#[derive(Default)]
+pub struct NavigationGrids {
+    pub grids: HashMap<RoomPos, NavigationGrid>,
+}
+
+// --- Functions ---
+/// Find the shortest path between two tiles with A*
+///
+/// Diagonal steps are only allowed if both adjacent straight tiles are walkable,
+/// so entities don't get stuck at the corners of walls.
+///
+/// # Arguments
+/// * `grid` - The navigation grid of the room
+/// * `start` - The tile where the path starts
+/// * `goal` - The tile where the path ends
+///
+/// # Returns
+/// The tiles of the path without the start tile, or `None` if the goal can't be reached
+pub fn find_path(grid: &NavigationGrid, start: TilePos, goal: TilePos) -> Option<Vec<TilePos>> {
+    if !grid.is_walkable(goal) {
+        return None;
+    }
+
+    // Tiles which are still to be checked, ordered by their estimated total cost
+    let mut open: BinaryHeap<Reverse<(u32, u32, TilePos)>> = BinaryHeap::new();
+    let mut came_from: HashMap<TilePos, TilePos> = HashMap::new();
+    let mut costs: HashMap<TilePos, u32> = HashMap::new();
+
+    open.push(Reverse((heuristic(start, goal), 0, start)));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((_, cost, tile))) = open.pop() {
+        if tile == goal {
+            return Some(reconstruct_path(&came_from, start, goal));
+        }
+
+        // Skip tiles which were already reached with a lower cost
+        if cost > costs[&tile] {
+            continue;
+        }
+
+        for (neighbor, step_cost) in get_neighbors(grid, tile) {
+            let new_cost = cost + step_cost;
+
+            let is_shorter = match costs.get(&neighbor) {
+                Some(&old_cost) => new_cost < old_cost,
+                None => true,
+            };
+
+            if is_shorter {
+                costs.insert(neighbor, new_cost);
+                came_from.insert(neighbor, tile);
+                open.push(Reverse((
+                    new_cost + heuristic(neighbor, goal),
+                    new_cost,
+                    neighbor,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+/// Get the walkable neighbors of a tile and the cost to step on them
+fn get_neighbors(grid: &NavigationGrid, tile: TilePos) -> Vec<(TilePos, u32)> {
+    let mut neighbors = vec![];
+
+    for y in -1..=1 {
+        for x in -1..=1 {
+            if x == 0 && y == 0 {
+                continue;
+            }
+
+            let neighbor = TilePos::new(tile.x + x, tile.y + y);
+            if !grid.is_walkable(neighbor) {
+                continue;
+            }
+
+            if x != 0 && y != 0 {
+                // Don't cut corners of walls
+                if grid.is_walkable(TilePos::new(tile.x + x, tile.y))
+                    && grid.is_walkable(TilePos::new(tile.x, tile.y + y))
+                {
+                    neighbors.push((neighbor, DIAGONAL_COST));
+                }
+            } else {
+                neighbors.push((neighbor, STRAIGHT_COST));
+            }
+        }
+    }
+
+    neighbors
+}
+
+/// Estimate the cost between two tiles (octile distance)
+fn heuristic(from: TilePos, to: TilePos) -> u32 {
+    let dx = (from.x - to.x).unsigned_abs();
+    let dy = (from.y - to.y).unsigned_abs();
+
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+/// Follow the found connections back from the goal to the start
+fn reconstruct_path(
+    came_from: &HashMap<TilePos, TilePos>,
+    start: TilePos,
+    goal: TilePos,
+) -> Vec<TilePos> {
+    let mut path = vec![];
+    let mut current = goal;
+
+    while current != start {
+        path.push(current);
+        current = came_from[&current];
+    }
+
+    path.reverse();
+    path
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Build a navigation grid from rows of characters, the first row is the top of the room
+    #[allow(dead_code)]
+    fn grid_from_rows(rows: &[&str]) -> NavigationGrid {
+        let tiles = rows
+            .iter()
+            .rev()
+            .map(|row| {
+                row.chars()
+                    .map(|character| match character {
+                        'X' => TileType::Wall,
+                        'O' => TileType::Pit,
+                        'R' => TileType::Rock,
+                        '^' => TileType::Spikes,
+                        '~' => TileType::Sludge,
+                        _ => TileType::Empty,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        NavigationGrid::new(tiles, Vec2::new(0.0, 0.0))
+    }
+
+    /// Test if a free straight line is found directly
+    #[test]
+    fn test_straight_path() {
+        let grid = grid_from_rows(&["#####", "#####", "#####"]);
+
+        let path = find_path(&grid, TilePos::new(0, 1), TilePos::new(4, 1)).unwrap();
+
+        assert_eq!(
+            path,
+            vec![
+                TilePos::new(1, 1),
+                TilePos::new(2, 1),
+                TilePos::new(3, 1),
+                TilePos::new(4, 1)
+            ]
+        );
+    }
+
+    /// Test if the path leads around a wall
+    #[test]
+    fn test_path_around_wall() {
+        let grid = grid_from_rows(&["#####", "##X##", "##X##", "##X##", "#####"]);
+
+        let path = find_path(&grid, TilePos::new(0, 2), TilePos::new(4, 2)).unwrap();
+
+        assert_eq!(path.last(), Some(&TilePos::new(4, 2)));
+        for tile in path.iter() {
+            assert!(grid.is_walkable(*tile));
+        }
+        // The path must go over the top or the bottom of the wall
+        assert!(path
+            .iter()
+            .any(|tile| tile.x == 2 && (tile.y == 0 || tile.y == 4)));
+    }
+
+    /// Test if pits and rocks block the path, while the floor hazards can be walked on
+    #[test]
+    fn test_hazard_tiles() {
+        let grid = grid_from_rows(&["#O#", "#R#", "#^~"]);
+
+        assert!(!grid.is_walkable(TilePos::new(1, 2)));
+        assert!(!grid.is_walkable(TilePos::new(1, 1)));
+        assert_eq!(
+            find_path(&grid, TilePos::new(0, 0), TilePos::new(2, 0)),
+            Some(vec![TilePos::new(1, 0), TilePos::new(2, 0)])
+        );
+    }
+
+    /// Test if no corners of walls are cut
+    #[test]
+    fn test_no_corner_cutting() {
+        let grid = grid_from_rows(&["#X", "##"]);
+
+        let path = find_path(&grid, TilePos::new(0, 0), TilePos::new(1, 0)).unwrap();
+        assert_eq!(path, vec![TilePos::new(1, 0)]);
+
+        let grid = grid_from_rows(&["X#", "#X"]);
+        assert_eq!(
+            find_path(&grid, TilePos::new(0, 0), TilePos::new(1, 1)),
+            None
+        );
+    }
+
+    /// Test if unreachable goals return no path
+    #[test]
+    fn test_unreachable_goal() {
+        let grid = grid_from_rows(&["##X##", "##X##", "##X##"]);
+
+        assert_eq!(
+            find_path(&grid, TilePos::new(0, 1), TilePos::new(4, 1)),
+            None
+        );
+        assert_eq!(
+            find_path(&grid, TilePos::new(0, 1), TilePos::new(2, 1)),
+            None
+        );
+    }
+
+    /// Test if the path to the start tile is empty
+    #[test]
+    fn test_path_to_start() {
+        let grid = grid_from_rows(&["###"]);
+
+        assert_eq!(
+            find_path(&grid, TilePos::new(1, 0), TilePos::new(1, 0)),
+            Some(vec![])
+        );
+    }
+
+    /// Test the conversion between world positions and tiles
+    #[test]
+    fn test_world_to_tile() {
+        let mut grid = grid_from_rows(&["###", "###"]);
+        grid.offset = Vec2::new(100.0, 200.0);
+
+        let tile = TilePos::new(2, 1);
+        let position = grid.tile_to_world(tile);
+
+        assert_eq!(
+            position,
+            Vec2::new(100.0 + 2.0 * TILE_SIZE, 200.0 + TILE_SIZE)
+        );
+        assert_eq!(grid.world_to_tile(position), tile);
+        assert_eq!(
+            grid.world_to_tile(position + Vec2::new(TILE_SIZE * 0.4, -TILE_SIZE * 0.4)),
+            tile
+        );
+    }
+}
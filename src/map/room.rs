@@ -1,15 +1,21 @@
 // --- Imports ---
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     menu::AppState,
+    rng::GameRng,
     spawnable::{
+        collision::CollisionEvent,
         enemy::{
             behavior::{Enemy, EnemySlainEvent},
             enemy_types::{spawn_enemy_type, EnemyResource},
         },
+        item::{spawn_item, ItemResource, ITEM_DROP_CHANCE},
         movement::Collider,
-        weapon::WeaponResource,
+        player::Player,
+        weapon::{spawn_weapon_pickup, WeaponResource, WEAPON_DROP_CHANCE},
     },
 };
 
@@ -17,6 +23,7 @@ use super::{
     calc_mid_room_pos,
     map_generation::{Neighbors, RoomInformation, RoomPos},
     room_generation::Spawner,
+    shop::spawn_shop_offers,
     ROOM_HEIGHT, TILE_SIZE, X_ROOM_LENGTH, Y_ROOM_LENGTH,
 };
 
@@ -27,16 +34,26 @@ impl Plugin for RoomPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<RoomFinishedEvent>()
             .add_event::<LeaveRoomEvent>()
+            .add_event::<SecretWallHitEvent>()
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
                     .with_system(finished_room)
                     .with_system(open_doors)
+                    .with_system(activate_spawned_rooms)
+                    .with_system(collision_triggers.after("step2"))
+                    .with_system(secret_wall_hit)
                     .with_system(enemy_slain.after("despawn"))
                     .with_system(room_transit),
             );
     }
 }
 
+// --- Constants ---
+/// Number of player bullet hits until a secret wall is destroyed
+pub const SECRET_WALL_HITS: i32 = 3;
+/// Score points the player gets for finding a secret room
+pub const SECRET_ROOM_SCORE: i32 = 500;
+
 // --- Structs ---
 
 /// Type of the room
@@ -47,12 +64,20 @@ impl Plugin for RoomPlugin {
 /// * `Empty` - Empty room so the player can rest for a wile
 /// * `Normal` - Standard room with enemies
 /// * `Boss` - Room where the boss will wait to complete the stage
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Component)]
+/// * `Treasure` - Room at a dead end with an item for the player
+/// * `Shop` - Room at a dead end where the player can buy a life and an item with score
+/// * `Secret` - Hidden room behind a secret wall which rewards the player with score
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Component, Serialize, Deserialize,
+)]
 pub enum RoomType {
     Start,
     Empty,
     Normal,
     Boss,
+    Treasure,
+    Shop,
+    Secret,
 }
 
 /// Status of the room
@@ -62,7 +87,7 @@ pub enum RoomType {
 /// * `Closed` - Room is closed and can be opened by the player
 /// * `Open`   - Room is open and can be completed by the player
 /// * `Finished` - Room is finished and can't be opened again
-#[derive(Clone, Component, PartialEq, PartialOrd, Debug)]
+#[derive(Clone, Component, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
 pub enum RoomStatus {
     Closed,
     Active,
@@ -77,12 +102,22 @@ pub enum RoomStatus {
 /// * `Empty` - No tile
 /// * `Door` - Door tile which will be opened if a room is completed
 /// * `Spawner` - Spawner tile to spawn a specific enemy
+/// * `SecretWall` - Wall tile in front of a secret room which can be destroyed by the player
+/// * `Spikes` - Floor tile which damages the player
+/// * `Pit` - Tile which blocks walking, but not bullets
+/// * `Sludge` - Floor tile which slows the player and the walking enemies
+/// * `Rock` - Tile which blocks walking and bullets and breaks after some hits of the player
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Component)]
 pub enum TileType {
     Empty,
     Wall,
     Door,
     Spawner,
+    SecretWall,
+    Spikes,
+    Pit,
+    Sludge,
+    Rock,
 }
 
 /// Transition direction of the room transition
@@ -119,6 +154,14 @@ pub struct Door;
 #[derive(Component)]
 pub struct Room;
 
+/// Secret wall component
+///
+/// The wall in front of a secret room will be destroyed after the remaining hits of the player
+#[derive(Component)]
+pub struct SecretWall {
+    pub hits: i32,
+}
+
 // --- Events ---
 /// Event to signal that the room is finished
 ///
@@ -130,6 +173,11 @@ pub struct RoomFinishedEvent(pub RoomPos);
 /// This event is sent when the player left the room to make the new room active
 pub struct LeaveRoomEvent(pub RoomPos, pub TransitionDirection);
 
+/// Event to signal that a secret wall was hit
+///
+/// This event is sent when a bullet of the player hits a secret wall
+pub struct SecretWallHitEvent(pub Entity);
+
 // --- Bundles ---
 
 /// Bundle with basic components for a room
@@ -149,13 +197,12 @@ struct RoomBundle {
 /// # Arguments
 /// `commands` - Commands to spawn the room
 /// `room_information` - Information about the room
-pub fn spawn_room(commands: &mut Commands, room_information: &RoomInformation) {
-    // If the room is a start room set it as active
-    let room_status = match room_information.room_type {
-        RoomType::Start => RoomStatus::Active,
-        _ => RoomStatus::Closed,
-    };
-
+/// `room_status` - Status of the room
+pub fn spawn_room(
+    commands: &mut Commands,
+    room_information: &RoomInformation,
+    room_status: RoomStatus,
+) {
     commands.spawn_bundle(RoomBundle {
         room_type: room_information.room_type,
         room_status,
@@ -260,21 +307,24 @@ fn spawn_room_transition_tile(
 // --- Events ---
 /// Room transition event of a room
 ///
-/// The room will activate it self if its not already active
+/// The room will activate it self if its not already active, execute the behavior of its room type
 /// and finish it self directly if no enemy will be spawned`
 fn room_transit(
     mut commands: Commands,
     mut ev_leave_room: EventReader<LeaveRoomEvent>,
     mut ev_room_finished: EventWriter<RoomFinishedEvent>,
-    room_query: Query<(&RoomStatus, &RoomPos, Entity), With<Room>>,
+    room_query: Query<(&RoomStatus, &RoomPos, &RoomType, Entity), With<Room>>,
+    mut player_query: Query<&mut Player>,
     enemy_query: Query<(&RoomPos, Entity), With<Enemy>>,
     spawner_query: Query<(&Transform, &Spawner, &RoomPos, Entity)>,
     enemy_res: Res<EnemyResource>,
     weapon_res: Res<WeaponResource>,
+    item_res: Res<ItemResource>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev_leave_room in ev_leave_room.iter() {
         // Go through all rooms and check if its the specified room of the event and is not already active
-        for (room_status, room_pos, room) in room_query.iter() {
+        for (room_status, room_pos, room_type, room) in room_query.iter() {
             if *room_pos == ev_leave_room.0 && *room_status == RoomStatus::Closed {
                 let mut r = commands.entity(room);
 
@@ -282,6 +332,16 @@ fn room_transit(
                 r.remove::<RoomStatus>();
                 r.insert(RoomStatus::Active);
 
+                let mut player = player_query.single_mut();
+                enter_room(
+                    &mut commands,
+                    room_type,
+                    room_pos,
+                    &mut player,
+                    &item_res,
+                    &mut rng,
+                );
+
                 // Check for living enemies or enemies that will be spawned.
                 // If no enemy is living or will be spawned finish the room directly
                 if !check_for_enemies(&enemy_query, room_pos, None)
@@ -300,6 +360,41 @@ fn room_transit(
     }
 }
 
+/// Execute the behavior of a room type when the player enters the room for the first time
+///
+/// * `Treasure` - Spawns a random item in the center of the room
+/// * `Shop` - Spawns a heart and an item which the player can buy with score
+/// * `Secret` - Rewards the player with score
+fn enter_room(
+    commands: &mut Commands,
+    room_type: &RoomType,
+    room_pos: &RoomPos,
+    player: &mut Player,
+    item_res: &ItemResource,
+    rng: &mut GameRng,
+) {
+    match room_type {
+        RoomType::Treasure => {
+            if let Some(item_type) = item_res.random_item(rng) {
+                spawn_item(
+                    commands,
+                    item_res,
+                    item_type,
+                    calc_mid_room_pos(room_pos.x, room_pos.y),
+                    *room_pos,
+                );
+            }
+        }
+        RoomType::Shop => {
+            spawn_shop_offers(commands, item_res, *room_pos, rng);
+        }
+        RoomType::Secret => {
+            player.score += SECRET_ROOM_SCORE;
+        }
+        _ => {}
+    }
+}
+
 /// Spawn enemies at the spawner position and return true if enemies were spawned
 fn spawn_enemies(
     commands: &mut Commands,
@@ -320,6 +415,7 @@ fn spawn_enemies(
                 &spawner.enemy_type,
                 Vec2::new(transform.translation.x, transform.translation.y),
                 *room_pos,
+                spawner.state.as_ref(),
             );
 
             commands.entity(entity).remove::<Spawner>();
@@ -373,20 +469,125 @@ fn check_for_enemies(
     false
 }
 
+/// Finish or activate rooms directly after they were spawned
+///
+/// The start room has no enemies, so its doors will be opened as soon as the room exists.
+/// Rooms of a resumed run open their doors if they were finished, and spawn their enemies again if they were active.
+fn activate_spawned_rooms(
+    mut commands: Commands,
+    mut ev_room_finished: EventWriter<RoomFinishedEvent>,
+    query: Query<(&RoomType, &RoomStatus, &RoomPos), Added<Room>>,
+    spawner_query: Query<(&Transform, &Spawner, &RoomPos, Entity)>,
+    enemy_res: Res<EnemyResource>,
+    weapon_res: Res<WeaponResource>,
+) {
+    for (room_type, room_status, room_pos) in query.iter() {
+        let finished = match room_status {
+            RoomStatus::Closed => false,
+            RoomStatus::Active if *room_type != RoomType::Start => !spawn_enemies(
+                &mut commands,
+                &spawner_query,
+                room_pos,
+                &enemy_res,
+                &weapon_res,
+            ),
+            _ => true,
+        };
+
+        if finished {
+            ev_room_finished.send(RoomFinishedEvent(*room_pos));
+        }
+    }
+}
+
 /// Finish room if event is triggered
 ///
-/// The room will finish if the event is triggered by changing the room status
+/// The room will finish if the event is triggered by changing the room status.
+/// Finished normal rooms have a chance to drop an item and a weapon in their center.
 fn finished_room(
     mut commands: Commands,
     mut ev_room_finished: EventReader<RoomFinishedEvent>,
-    query: Query<(&RoomStatus, &RoomPos, Entity), With<Room>>,
+    query: Query<(&RoomStatus, &RoomPos, &RoomType, Entity), With<Room>>,
+    item_res: Res<ItemResource>,
+    weapon_res: Res<WeaponResource>,
+    mut rng: ResMut<GameRng>,
 ) {
+    // The status is changed at the end of the frame, so a room can be finished multiple times in one frame
+    let mut finished_rooms: Vec<RoomPos> = vec![];
+
     for ev_room_finished in ev_room_finished.iter() {
-        for (room_status, room_pos, room) in query.iter() {
-            if room_pos == &ev_room_finished.0 && *room_status == RoomStatus::Active {
+        for (room_status, room_pos, room_type, room) in query.iter() {
+            if room_pos == &ev_room_finished.0
+                && *room_status == RoomStatus::Active
+                && !finished_rooms.contains(room_pos)
+            {
+                finished_rooms.push(*room_pos);
+
                 let mut r = commands.entity(room);
                 r.remove::<RoomStatus>();
                 r.insert(RoomStatus::Finished);
+
+                let mid_room_pos = calc_mid_room_pos(room_pos.x, room_pos.y);
+
+                if *room_type == RoomType::Normal && rng.gen::<f32>() < ITEM_DROP_CHANCE {
+                    if let Some(item_type) = item_res.random_item(&mut *rng) {
+                        spawn_item(&mut commands, &item_res, item_type, mid_room_pos, *room_pos);
+                    }
+                }
+                if *room_type == RoomType::Normal && rng.gen::<f32>() < WEAPON_DROP_CHANCE {
+                    spawn_weapon_pickup(
+                        &mut commands,
+                        &weapon_res,
+                        rng.gen(),
+                        mid_room_pos + Vec2::new(TILE_SIZE, 0.0),
+                        *room_pos,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Sends the room events of collisions with room transitions and secret walls
+fn collision_triggers(
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_leave_room: EventWriter<LeaveRoomEvent>,
+    mut ev_secret_wall_hit: EventWriter<SecretWallHitEvent>,
+    transition_query: Query<(&RoomPos, &TransitionDirection)>,
+) {
+    for ev in ev_collision.iter() {
+        if ev.triggers(Collider::RoomTransition) {
+            if let Ok((room_pos, direction)) = transition_query.get(ev.other) {
+                ev_leave_room.send(LeaveRoomEvent(*room_pos, *direction));
+            }
+        } else if ev.triggers(Collider::SecretWall) {
+            ev_secret_wall_hit.send(SecretWallHitEvent(ev.other));
+        }
+    }
+}
+
+/// Destroy a secret wall after it was hit often enough
+///
+/// The destroyed secret wall will be opened like a door, so the player can enter the secret room
+fn secret_wall_hit(
+    mut commands: Commands,
+    mut ev_secret_wall_hit: EventReader<SecretWallHitEvent>,
+    mut query: Query<&mut SecretWall>,
+) {
+    for ev in ev_secret_wall_hit.iter() {
+        if let Ok(mut secret_wall) = query.get_mut(ev.0) {
+            secret_wall.hits -= 1;
+
+            if secret_wall.hits == 0 {
+                let mut entity = commands.entity(ev.0);
+                entity
+                    .remove::<SecretWall>()
+                    .remove::<Collider>()
+                    .remove::<Sprite>();
+                entity.insert(Sprite {
+                    color: Color::rgb(0.0, 0.0, 0.0),
+                    ..Default::default()
+                });
             }
         }
     }
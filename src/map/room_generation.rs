@@ -1,147 +1,271 @@
 // --- Imports ---
 use super::{
+    hazard::{Rock, ROCK_HITS},
     map_generation::{Neighbors, RoomInformation, RoomPos},
-    room::{spawn_room, Door, RoomType, TileType},
+    pathfinding::NavigationGrid,
+    room::{spawn_room, Door, RoomStatus, RoomType, SecretWall, TileType, SECRET_WALL_HITS},
     ROOM_HEIGHT, TILE_SIZE, X_ROOM_LENGTH, Y_ROOM_LENGTH,
 };
 use bevy::prelude::*;
-use rand::Rng;
-use std::{
-    fs::{read_dir, File},
-    io::{BufRead, BufReader},
-};
+use rand::seq::SliceRandom;
+use ron::{de::from_str, error::SpannedError};
+use serde::Deserialize;
+use std::collections::HashMap;
 
-use crate::spawnable::{enemy::enemy_types::EnemyType, movement::Collider};
+use crate::{
+    rng::GameRng,
+    save::EnemyState,
+    spawnable::{enemy::enemy_types::EnemyType, movement::Collider},
+};
 
 // --- Components ---
-/// Component to identify a room
+/// Spawner component which spawns an enemy of its type when the player enters its room
+///
+/// * `enemy_type` - The type of the enemy
+/// * `state` - The saved state of an enemy which was alive when the run was saved
 #[derive(Component, Debug, Clone)]
 pub struct Spawner {
     pub enemy_type: EnemyType,
+    pub state: Option<EnemyState>,
+}
+
+// --- Constants ---
+/// Characters of the tiles in room files, other characters are spawners of the legend or empty floor
+pub const TILE_GLYPHS: [char; 7] = ['#', 'X', 'S', '^', 'O', '~', 'R'];
+/// Line which separates the header of a room file from the map
+pub const HEADER_SEPARATOR: &str = "---";
+
+// --- Structs ---
+/// Optional header of a room file
+///
+/// * `spawners` - Legend of characters which are spawners with a weighted pool of enemy types
+/// * `weight` - Weight of the room when a room of its type is chosen
+/// * `min_floor` - First floor the room can be chosen on
+/// * `tags` - Tags to describe the room
+#[derive(Deserialize)]
+pub struct RoomHeader {
+    #[serde(default)]
+    pub spawners: HashMap<char, Vec<(EnemyType, u32)>>,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default = "default_min_floor")]
+    pub min_floor: i32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Default for RoomHeader {
+    fn default() -> Self {
+        RoomHeader {
+            spawners: HashMap::new(),
+            weight: default_weight(),
+            min_floor: default_min_floor(),
+            tags: vec![],
+        }
+    }
+}
+
+/// Parsed room file
+///
+/// * `tiles` - Tiles of the room without the surrounding walls, indexed by `[y][x]`
+/// * `spawner_pools` - Weighted enemy pools of the spawners of the legend, indexed by their `(x, y)` tile
+/// * `weight` - Weight of the room when a room of its type is chosen
+/// * `min_floor` - First floor the room can be chosen on
+/// * `tags` - Tags of the header
+#[derive(Clone, Debug)]
+pub struct RoomTemplate {
+    pub tiles: Vec<Vec<TileType>>,
+    pub spawner_pools: HashMap<(usize, usize), Vec<(EnemyType, u32)>>,
+    pub weight: u32,
+    pub min_floor: i32,
+    pub tags: Vec<String>,
 }
 
 // --- Room generation ---
-/// Create a new random room with the given information's
+/// Create a new room with the given information's
 ///
-/// A random room will be created with the specified position and room type
+/// The room will be created from its room file with the specified position and room type
 ///
 /// # Arguments
 /// `commands` - The commands to add the room to the world
 /// `room_information` - The information of the room
 /// `offset` - The offset of the room
-pub fn create_room(commands: &mut Commands, room_information: &RoomInformation, offset: Vec2) {
-    let mut map = create_room_map(room_information.room_type);
-    map = add_walls(map, room_information.neighbors);
+/// `room` - The room file
+/// `floor` - The number of the floor
+/// `rng` - The random number generator of the run
+///
+/// # Returns
+/// The navigation grid of the room
+pub fn create_room(
+    commands: &mut Commands,
+    room_information: &RoomInformation,
+    offset: Vec2,
+    room: &RoomTemplate,
+    floor: i32,
+    rng: &mut GameRng,
+) -> NavigationGrid {
+    let map = add_walls(
+        room.tiles.clone(),
+        room_information.neighbors,
+        room_information.secret_neighbors,
+        Neighbors::new(),
+    );
 
-    spawn_room_map(
+    spawn_room_map(commands, &map, offset, &room_information.position);
+    spawn_room_spawners(
         commands,
-        map,
+        &map,
+        &room.spawner_pools,
         offset,
-        &room_information.position,
-        &room_information.room_type,
+        room_information,
+        floor,
+        rng,
     );
-    spawn_room(commands, room_information)
+
+    // The start room is active from the beginning
+    let room_status = match room_information.room_type {
+        RoomType::Start => RoomStatus::Active,
+        _ => RoomStatus::Closed,
+    };
+    spawn_room(commands, room_information, room_status);
+
+    NavigationGrid::new(map, offset)
 }
 
-/// Generate a random room map
+/// Create a room of a saved run again
 ///
-/// Get and random room of the defined rooms and convert it to an two dimensional array with specific tile types
+/// The room is built from its saved room file, the spawners are restored separately
 ///
 /// # Arguments
-/// `room_type` - The type of the room
+/// `commands` - The commands to add the room to the world
+/// `room_information` - The information of the room
+/// `offset` - The offset of the room
+/// `template` - The room file
+/// `room_status` - The saved status of the room
+/// `found_secret_neighbors` - The secret neighbors whose secret wall was already destroyed
 ///
 /// # Returns
-/// A two dimensional array with the room's tiles
-fn create_room_map(room_type: RoomType) -> Vec<Vec<TileType>> {
-    let file = get_random_room(room_type);
-
-    let mut map = vec![vec![TileType::Empty; X_ROOM_LENGTH - 2]; Y_ROOM_LENGTH - 2];
+/// The navigation grid of the room
+pub fn restore_room(
+    commands: &mut Commands,
+    room_information: &RoomInformation,
+    offset: Vec2,
+    template: &RoomTemplate,
+    room_status: RoomStatus,
+    found_secret_neighbors: Neighbors,
+) -> NavigationGrid {
+    let map = add_walls(
+        template.tiles.clone(),
+        room_information.neighbors,
+        room_information.secret_neighbors,
+        found_secret_neighbors,
+    );
 
-    for (y, line) in BufReader::new(file).lines().enumerate() {
-        if let Ok(line) = line {
-            for (x, character) in line.chars().enumerate() {
-                match character {
-                    'X' => map[Y_ROOM_LENGTH - y - 3][x] = TileType::Wall,
-                    'S' => map[Y_ROOM_LENGTH - y - 3][x] = TileType::Spawner,
-                    _ => map[Y_ROOM_LENGTH - y - 3][x] = TileType::Empty,
-                }
-                if x >= X_ROOM_LENGTH - 3 {
-                    break;
-                };
-            }
-            if y >= Y_ROOM_LENGTH - 3 {
-                break;
-            };
-        }
-    }
+    spawn_room_map(commands, &map, offset, &room_information.position);
+    spawn_room(commands, room_information, room_status);
 
-    map
+    NavigationGrid::new(map, offset)
 }
 
-/// Get a random room by its room type
+/// Parse a room file and convert it to an two dimensional array with specific tile types
 ///
-/// Read the room files in the `rooms` folder and return a random room file
+/// A room file can start with a header in ron, which is separated from the map by a line with `---`.
+/// Unknown characters of the map are empty floor, unless the legend of the header maps them to a spawner.
+///
+/// * `#` - Floor
+/// * `X` - Wall
+/// * `S` - Spawner with a random enemy
+/// * `^` - Spikes
+/// * `O` - Pit
+/// * `~` - Sludge
+/// * `R` - Rock
 ///
 /// # Arguments
-/// * `room_type` - The room type to get a random room for
+/// `text` - The content of the room file
 ///
 /// # Returns
-/// The random room file
-fn get_random_room(room_type: RoomType) -> File {
-    // Build directory path
-    let mut directory = "assets/rooms/".to_string();
-    match room_type {
-        RoomType::Start => directory.push_str("start"),
-        RoomType::Empty => directory.push_str("empty"),
-        RoomType::Normal => directory.push_str("normal"),
-        RoomType::Boss => directory.push_str("boss"),
-    }
+/// The parsed room, or the error of the header
+pub fn parse_room(text: &str) -> Result<RoomTemplate, SpannedError> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    let header = match lines
+        .iter()
+        .position(|line| line.trim() == HEADER_SEPARATOR)
+    {
+        Some(separator) => {
+            let header = from_str::<RoomHeader>(&lines[..separator].join("\n"))?;
+            lines.drain(..=separator);
+            header
+        }
+        None => RoomHeader::default(),
+    };
 
-    // Read all files in the directory
-    let file_list_iter = read_dir(directory.clone());
-    if let Err(err) = file_list_iter {
-        panic!("Could not read directory: {}", err);
-    }
+    let mut map = vec![vec![TileType::Empty; X_ROOM_LENGTH - 2]; Y_ROOM_LENGTH - 2];
+    let mut spawner_pools = HashMap::new();
 
-    // Add Files to the list
-    let mut file_list = Vec::new();
-    for file in file_list_iter.unwrap() {
-        if let Err(err) = file {
-            panic!("Could not read file: {}", err);
+    for (y, line) in lines.iter().enumerate() {
+        for (x, character) in line.chars().enumerate() {
+            let tile = &mut map[Y_ROOM_LENGTH - y - 3][x];
+            match character {
+                'X' => *tile = TileType::Wall,
+                'S' => *tile = TileType::Spawner,
+                '^' => *tile = TileType::Spikes,
+                'O' => *tile = TileType::Pit,
+                '~' => *tile = TileType::Sludge,
+                'R' => *tile = TileType::Rock,
+                _ => match header.spawners.get(&character) {
+                    Some(pool) => {
+                        *tile = TileType::Spawner;
+                        spawner_pools.insert((x, Y_ROOM_LENGTH - y - 3), pool.clone());
+                    }
+                    None => *tile = TileType::Empty,
+                },
+            }
+            if x >= X_ROOM_LENGTH - 3 {
+                break;
+            };
         }
-
-        file_list.push(file.unwrap().path());
+        if y >= Y_ROOM_LENGTH - 3 {
+            break;
+        };
     }
 
-    // Panic if there are no files
-    if file_list.is_empty() {
-        panic!("No files found in directory: {}", directory);
-    }
+    Ok(RoomTemplate {
+        tiles: map,
+        spawner_pools,
+        weight: header.weight,
+        min_floor: header.min_floor,
+        tags: header.tags,
+    })
+}
 
-    // Get a random file
-    let mut rng = rand::thread_rng();
-    let file_path = file_list[rng.gen_range(0..file_list.len())].to_str();
-    if file_path.is_none() {
-        panic!("Could not convert file path to string");
-    }
+/// Default weight of a room
+fn default_weight() -> u32 {
+    1
+}
 
-    // Open the file and return it
-    let file = File::open(file_path.unwrap());
-    if let Err(err) = file {
-        panic!("Could not open file: {}", err);
-    }
-    file.unwrap()
+/// Default minimum floor of a room
+fn default_min_floor() -> i32 {
+    1
 }
 
 /// Surround the room with walls and doors on each side
 ///
+/// Sides to a secret room get a secret wall instead of a door, or an opening if the secret room was already found
+///
 /// # Arguments
 /// * `map` - The room map to add walls to
 /// * `neighbors` - The neighbors of the room
+/// * `secret_neighbors` - The neighbors of the room which are secret rooms
+/// * `found_secret_neighbors` - The secret neighbors whose secret wall was already destroyed
 ///
 /// # Returns
 /// The room map with walls and doors
-fn add_walls(map: Vec<Vec<TileType>>, neighbors: Neighbors) -> Vec<Vec<TileType>> {
+fn add_walls(
+    map: Vec<Vec<TileType>>,
+    neighbors: Neighbors,
+    secret_neighbors: Neighbors,
+    found_secret_neighbors: Neighbors,
+) -> Vec<Vec<TileType>> {
     // Initialize a new two dimensional array with the same size as the map plus one row on each side
     let mut new_map = vec![vec![TileType::Wall; X_ROOM_LENGTH]; Y_ROOM_LENGTH];
 
@@ -153,17 +277,29 @@ fn add_walls(map: Vec<Vec<TileType>>, neighbors: Neighbors) -> Vec<Vec<TileType>
     }
 
     // Add on door on each side
+    let door = |secret: bool, found: bool| {
+        if found {
+            TileType::Empty
+        } else if secret {
+            TileType::SecretWall
+        } else {
+            TileType::Door
+        }
+    };
     if neighbors.top {
-        new_map[Y_ROOM_LENGTH - 1][X_ROOM_LENGTH / 2] = TileType::Door;
+        new_map[Y_ROOM_LENGTH - 1][X_ROOM_LENGTH / 2] =
+            door(secret_neighbors.top, found_secret_neighbors.top);
     }
     if neighbors.bottom {
-        new_map[0][X_ROOM_LENGTH / 2] = TileType::Door;
+        new_map[0][X_ROOM_LENGTH / 2] =
+            door(secret_neighbors.bottom, found_secret_neighbors.bottom);
     }
     if neighbors.left {
-        new_map[Y_ROOM_LENGTH / 2][0] = TileType::Door;
+        new_map[Y_ROOM_LENGTH / 2][0] = door(secret_neighbors.left, found_secret_neighbors.left);
     }
     if neighbors.right {
-        new_map[Y_ROOM_LENGTH / 2][X_ROOM_LENGTH - 1] = TileType::Door;
+        new_map[Y_ROOM_LENGTH / 2][X_ROOM_LENGTH - 1] =
+            door(secret_neighbors.right, found_secret_neighbors.right);
     }
 
     new_map
@@ -178,32 +314,104 @@ fn add_walls(map: Vec<Vec<TileType>>, neighbors: Neighbors) -> Vec<Vec<TileType>
 /// * `commands` - The commands to spawn the room with
 /// * `map` - The room map to spawn
 /// * `offset` - The offset to spawn the room at
-/// * `position` - The position of the room
-/// * `room_type` - The type of the room
+/// * `room_pos` - The position of the room
 fn spawn_room_map(
     commands: &mut Commands,
-    map: Vec<Vec<TileType>>,
+    map: &[Vec<TileType>],
     offset: Vec2,
     room_pos: &RoomPos,
-    room_type: &RoomType,
 ) {
     // iterate over map and spawn the tiles
     for (y, row) in map.iter().enumerate() {
         for (x, tile) in row.iter().enumerate() {
             let x_pos = x as f32 * TILE_SIZE + offset.x;
             let y_pos = y as f32 * TILE_SIZE + offset.y;
+            let is_boundary = x == 0 || y == 0 || x == row.len() - 1 || y == map.len() - 1;
 
             spawn_tile(
                 commands,
                 Vec3::new(x_pos, y_pos, ROOM_HEIGHT),
                 *tile,
                 room_pos,
-                room_type,
+                is_boundary,
             );
         }
     }
 }
 
+/// Spawn a spawner with a random enemy type on every spawner tile of a new room
+///
+/// Spawners of the legend of the room file choose from their pool, the other spawners of boss rooms always spawn the boss
+///
+/// # Arguments
+/// * `commands` - The commands to spawn the spawners with
+/// * `map` - The room map
+/// * `spawner_pools` - The enemy pools of the spawners of the legend
+/// * `offset` - The offset of the room
+/// * `room_information` - The information of the room
+/// * `floor` - The number of the floor
+/// * `rng` - The random number generator of the run
+fn spawn_room_spawners(
+    commands: &mut Commands,
+    map: &[Vec<TileType>],
+    spawner_pools: &HashMap<(usize, usize), Vec<(EnemyType, u32)>>,
+    offset: Vec2,
+    room_information: &RoomInformation,
+    floor: i32,
+    rng: &mut GameRng,
+) {
+    for (y, row) in map.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if *tile == TileType::Spawner {
+                // The room file has no surrounding walls
+                let pool = (x > 0 && y > 0)
+                    .then(|| spawner_pools.get(&(x - 1, y - 1)))
+                    .flatten();
+
+                let enemy_type = match pool {
+                    Some(pool) => pool
+                        .choose_weighted(rng, |(_, weight)| *weight)
+                        .map(|(enemy_type, _)| enemy_type.clone())
+                        .unwrap_or_else(|_| EnemyType::random_for_floor(floor, rng)),
+                    None if room_information.room_type == RoomType::Boss => EnemyType::Boss,
+                    None => EnemyType::random_for_floor(floor, rng),
+                };
+
+                spawn_spawner(
+                    commands,
+                    Vec2::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE) + offset,
+                    room_information.position,
+                    enemy_type,
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// Spawn a spawner which spawns an enemy when the player enters the room
+///
+/// # Arguments
+/// * `commands` - The commands to spawn the spawner with
+/// * `position` - The position of the spawner
+/// * `room_pos` - The position of the room
+/// * `enemy_type` - The type of the enemy to spawn
+/// * `state` - The saved state of the enemy, a new enemy is spawned without one
+pub fn spawn_spawner(
+    commands: &mut Commands,
+    position: Vec2,
+    room_pos: RoomPos,
+    enemy_type: EnemyType,
+    state: Option<EnemyState>,
+) {
+    commands
+        .spawn()
+        .insert(Transform::from_translation(position.extend(ROOM_HEIGHT)))
+        .insert(room_pos)
+        .insert(Name::new("Spawner"))
+        .insert(Spawner { enemy_type, state });
+}
+
 /// Spawn a tile with the given information
 ///
 /// A tile will be spawned with the specified position, tile type and room type
@@ -213,40 +421,71 @@ fn spawn_room_map(
 /// * `position` - The position of the tile
 /// * `tile_type` - The type of the tile
 /// * `room_pos` - The position of the room
-/// * `room_type` - The type of the room
+/// * `is_boundary` - If the tile is on the edge of the room, walls there keep flying enemies in the room
 fn spawn_tile(
     commands: &mut Commands,
     position: Vec3,
     tile_type: TileType,
     room_pos: &RoomPos,
-    room_type: &RoomType,
+    is_boundary: bool,
 ) {
     let color: Color;
     let name: String;
-    let collider: bool;
+    let collider: Option<Collider>;
 
     // Set color, name and collider based on tile type
     match tile_type {
         TileType::Empty => {
             color = Color::rgb(0.0, 0.0, 0.0);
             name = "Floor".to_string();
-            collider = false;
+            collider = None;
+        }
+        TileType::Wall if is_boundary => {
+            color = Color::rgb(0.8, 0.8, 0.8);
+            name = "Wall".to_string();
+            collider = Some(Collider::Boundary);
         }
         TileType::Wall => {
             color = Color::rgb(0.8, 0.8, 0.8);
             name = "Wall".to_string();
-            collider = true;
+            collider = Some(Collider::Solid);
         }
         TileType::Door => {
             color = Color::rgb(0.33, 0.18, 0.07);
 
             name = "Door".to_string();
-            collider = true;
+            collider = Some(Collider::Door);
         }
         TileType::Spawner => {
             color = Color::rgb(0.0, 0.0, 0.0);
             name = "Spawner".to_string();
-            collider = false;
+            collider = None;
+        }
+        TileType::SecretWall => {
+            // Looks like a normal wall to keep the room secret
+            color = Color::rgb(0.8, 0.8, 0.8);
+            name = "SecretWall".to_string();
+            collider = Some(Collider::SecretWall);
+        }
+        TileType::Spikes => {
+            color = Color::rgb(0.55, 0.1, 0.1);
+            name = "Spikes".to_string();
+            collider = None;
+        }
+        TileType::Pit => {
+            color = Color::rgb(0.1, 0.1, 0.25);
+            name = "Pit".to_string();
+            collider = Some(Collider::Pit);
+        }
+        TileType::Sludge => {
+            color = Color::rgb(0.25, 0.3, 0.1);
+            name = "Sludge".to_string();
+            collider = None;
+        }
+        TileType::Rock => {
+            color = Color::rgb(0.45, 0.35, 0.25);
+            name = "Rock".to_string();
+            collider = Some(Collider::Rock);
         }
     }
 
@@ -268,20 +507,47 @@ fn spawn_tile(
     tile.insert(*room_pos);
 
     // Add collider if needed
-    if collider {
-        tile.insert(Collider::Solid);
+    if let Some(collider) = collider {
+        tile.insert(collider);
     };
 
-    // Add door component or spawner components
+    // Add door component, secret wall component, rock component or the type of floor hazards
     if tile_type == TileType::Door {
         tile.insert(Door);
-    } else if tile_type == TileType::Spawner {
-        let mut enemy_type: EnemyType = rand::random();
+    } else if tile_type == TileType::SecretWall {
+        tile.insert(SecretWall {
+            hits: SECRET_WALL_HITS,
+        });
+    } else if tile_type == TileType::Rock {
+        tile.insert(Rock { hits: ROCK_HITS });
+    } else if matches!(tile_type, TileType::Spikes | TileType::Sludge) {
+        tile.insert(tile_type);
+    }
+}
 
-        if *room_type == RoomType::Boss {
-            enemy_type = EnemyType::Boss;
-        }
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if the characters of the legend become spawners with their enemy pool
+    #[test]
+    fn test_parse_room_legend() {
+        let text = "(\n\tspawners: {\n\t\t'b': [(BigBlob, 1)],\n\t},\n)\n---\nS#b\nX#r\n";
+
+        let room = parse_room(text).unwrap();
+        let top = Y_ROOM_LENGTH - 3;
+
+        assert_eq!(room.tiles[top][0], TileType::Spawner);
+        assert_eq!(room.tiles[top][2], TileType::Spawner);
+        assert_eq!(room.tiles[top - 1][0], TileType::Wall);
+        assert_eq!(room.tiles[top - 1][2], TileType::Empty);
+        assert_eq!(room.spawner_pools.len(), 1);
+        assert_eq!(room.spawner_pools[&(2, top)], vec![(EnemyType::BigBlob, 1)]);
 
-        tile.insert(Spawner { enemy_type });
+        // Rooms without a header keep working
+        let room = parse_room("S#b\n").unwrap();
+        assert_eq!(room.tiles[top][0], TileType::Spawner);
+        assert_eq!(room.tiles[top][2], TileType::Empty);
+        assert!(room.spawner_pools.is_empty());
     }
 }
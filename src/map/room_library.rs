@@ -0,0 +1,307 @@
+// --- Imports ---
+use bevy::asset::FileAssetIo;
+use rand::seq::SliceRandom;
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{rng::GameRng, spawnable::enemy::enemy_types::EnemyType};
+
+use super::{
+    map_generation::GenError,
+    room::RoomType,
+    room_generation::{parse_room, RoomTemplate},
+};
+
+// --- Constants ---
+/// Directory of the room files relative to the root of the assets
+pub const ROOM_DIRECTORY: &str = "assets/rooms";
+/// Directories of the room types, boss rooms are in a subdirectory for each floor
+const ROOM_TYPE_DIRECTORIES: [(RoomType, &str); 6] = [
+    (RoomType::Start, "start"),
+    (RoomType::Normal, "normal"),
+    (RoomType::Boss, "boss"),
+    (RoomType::Treasure, "treasure"),
+    (RoomType::Shop, "shop"),
+    (RoomType::Secret, "secret"),
+];
+
+// --- Resources ---
+/// Parsed room files, which are loaded once when the game starts
+///
+/// A template is named by its path, like `assets/rooms/normal/cross.txt`, which is stored in the saved runs.
+///
+/// * `templates` - The templates by their name
+/// * `groups` - The sorted names of the templates of every room type
+#[derive(Default)]
+pub struct RoomLibrary {
+    pub templates: HashMap<String, RoomTemplate>,
+    pub groups: HashMap<RoomType, Vec<String>>,
+}
+
+// --- Structs ---
+/// Error while loading the room library
+///
+/// * `Io` - A room directory or file couldn't be read
+/// * `Format` - The header of a room file is invalid
+/// * `Missing` - There is no room of a room type which every floor needs
+#[derive(Debug)]
+pub enum RoomLibraryError {
+    Io(PathBuf, io::Error),
+    Format(String, String),
+    Missing(RoomType),
+}
+
+impl fmt::Display for RoomLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoomLibraryError::Io(path, err) => {
+                write!(f, "Could not read {}: {}", path.display(), err)
+            }
+            RoomLibraryError::Format(name, err) => {
+                write!(f, "Could not parse the header of {}: {}", name, err)
+            }
+            RoomLibraryError::Missing(room_type) => {
+                write!(f, "There is no {:?} room for the first floor", room_type)
+            }
+        }
+    }
+}
+
+// --- Methods for Structs ---
+impl RoomLibrary {
+    /// Load the room files of every room type from the assets
+    ///
+    /// The assets are searched like the asset server does, in the directory of the crate
+    /// when the game is started with cargo and next to the executable otherwise.
+    pub fn load_assets() -> Result<RoomLibrary, RoomLibraryError> {
+        RoomLibrary::load(&FileAssetIo::get_root_path())
+    }
+
+    /// Load the room files of every room type
+    ///
+    /// Boss rooms are loaded from the subdirectory of their floor, which is used as their minimum floor.
+    ///
+    /// # Arguments
+    /// * `root` - The directory which contains the `assets` folder
+    ///
+    /// # Returns
+    /// The library, or an error if a file can't be read or parsed or a room type which every floor needs has no rooms
+    pub fn load(root: &Path) -> Result<RoomLibrary, RoomLibraryError> {
+        let mut library = RoomLibrary::default();
+
+        for (room_type, directory) in ROOM_TYPE_DIRECTORIES {
+            let name = format!("{}/{}", ROOM_DIRECTORY, directory);
+            let path = root.join(&name);
+
+            if room_type != RoomType::Boss {
+                library.load_directory(room_type, &path, &name, None)?;
+                continue;
+            }
+
+            for floor_path in read_sorted_dir(&path)? {
+                let floor = floor_path
+                    .file_name()
+                    .and_then(|floor| floor.to_str())
+                    .and_then(|floor| floor.parse::<i32>().ok());
+
+                if let (Some(floor), true) = (floor, floor_path.is_dir()) {
+                    let name = format!("{}/{}", name, floor);
+                    library.load_directory(room_type, &floor_path, &name, Some(floor))?;
+                }
+            }
+        }
+
+        // Every floor needs these rooms, so they have to exist from the first floor on
+        for (room_type, _) in ROOM_TYPE_DIRECTORIES {
+            if library.candidates(room_type, 1).is_empty() {
+                return Err(RoomLibraryError::Missing(room_type));
+            }
+        }
+
+        Ok(library)
+    }
+
+    /// Load the room files of a directory
+    ///
+    /// # Arguments
+    /// * `room_type` - The room type of the room files
+    /// * `path` - The path of the directory
+    /// * `name` - The name of the directory, which is the start of the names of its templates
+    /// * `min_floor` - The minimum floor which replaces the one of the headers
+    fn load_directory(
+        &mut self,
+        room_type: RoomType,
+        path: &Path,
+        name: &str,
+        min_floor: Option<i32>,
+    ) -> Result<(), RoomLibraryError> {
+        for file_path in read_sorted_dir(path)? {
+            let file_name = match file_path.file_name().and_then(|file| file.to_str()) {
+                Some(file_name) if file_name.ends_with(".txt") => file_name,
+                _ => continue,
+            };
+            let name = format!("{}/{}", name, file_name);
+
+            let text = fs::read_to_string(&file_path)
+                .map_err(|err| RoomLibraryError::Io(file_path.clone(), err))?;
+            let mut template = parse_room(&text)
+                .map_err(|err| RoomLibraryError::Format(name.clone(), err.to_string()))?;
+            if let Some(min_floor) = min_floor {
+                template.min_floor = min_floor;
+            }
+
+            self.templates.insert(name.clone(), template);
+            self.groups.entry(room_type).or_default().push(name);
+        }
+
+        Ok(())
+    }
+
+    /// Get the names of the templates which can be used on a floor
+    ///
+    /// Boss rooms of the deepest floor up to the given floor are used,
+    /// the other rooms can be used on every floor from their minimum floor on.
+    ///
+    /// # Arguments
+    /// * `room_type` - The room type of the templates
+    /// * `floor` - The number of the floor
+    fn candidates(&self, room_type: RoomType, floor: i32) -> Vec<&String> {
+        let mut names: Vec<&String> = self
+            .groups
+            .get(&room_type)
+            .into_iter()
+            .flatten()
+            .filter(|name| self.templates[*name].min_floor <= floor)
+            .collect();
+
+        if room_type == RoomType::Boss {
+            let deepest = names
+                .iter()
+                .map(|name| self.templates[*name].min_floor)
+                .max();
+            names.retain(|name| Some(self.templates[*name].min_floor) == deepest);
+        }
+
+        names
+    }
+
+    /// Choose a random template of a room type for a floor, weighted by the weights of the templates
+    ///
+    /// # Arguments
+    /// * `room_type` - The room type of the template
+    /// * `floor` - The number of the floor
+    /// * `rng` - The random number generator of the run
+    ///
+    /// # Returns
+    /// The name of the template, or an error if no template can be used on the floor
+    pub fn choose(
+        &self,
+        room_type: RoomType,
+        floor: i32,
+        rng: &mut GameRng,
+    ) -> Result<&str, GenError> {
+        self.candidates(room_type, floor)
+            .choose_weighted(rng, |name| self.templates[*name].weight)
+            .map(|name| name.as_str())
+            .map_err(|_| GenError::NoTemplate(room_type, floor))
+    }
+
+    /// Get a template by its name
+    ///
+    /// # Arguments
+    /// * `name` - The name of the template
+    ///
+    /// # Returns
+    /// The template, or an error if there is no template with this name
+    pub fn get(&self, name: &str) -> Result<&RoomTemplate, GenError> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| GenError::UnknownTemplate(name.to_string()))
+    }
+
+    /// Get the enemies of the spawner pools in the legends of the templates
+    ///
+    /// # Returns
+    /// The name of every template with an enemy of its legend, sorted by the name so the order is always the same
+    pub fn legend_enemies(&self) -> Vec<(&str, &EnemyType)> {
+        let mut enemies: Vec<(&str, &EnemyType)> = self
+            .templates
+            .iter()
+            .flat_map(|(name, template)| {
+                template
+                    .spawner_pools
+                    .values()
+                    .flatten()
+                    .map(move |(enemy_type, _)| (name.as_str(), enemy_type))
+            })
+            .collect();
+        enemies.sort_by_key(|(name, enemy_type)| (*name, format!("{:?}", enemy_type)));
+        enemies.dedup();
+
+        enemies
+    }
+}
+
+// --- Functions ---
+/// Read the entries of a directory sorted by their path, so the same seed picks the same rooms
+///
+/// # Arguments
+/// * `path` - The path of the directory
+fn read_sorted_dir(path: &Path) -> Result<Vec<PathBuf>, RoomLibraryError> {
+    let error = |err| RoomLibraryError::Io(path.to_path_buf(), err);
+
+    let mut entries = fs::read_dir(path)
+        .map_err(error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()
+        .map_err(error)?;
+    entries.sort();
+
+    Ok(entries)
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if the shipped rooms are loaded and chosen by their floor and weight
+    #[test]
+    fn test_room_library() {
+        let mut library = RoomLibrary::load(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+        let mut rng = GameRng::new(Some(3));
+
+        // Every floor has its own boss rooms
+        for floor in 1..=3 {
+            let name = library.choose(RoomType::Boss, floor, &mut rng).unwrap();
+            assert!(name.starts_with(&format!("{}/boss/{}/", ROOM_DIRECTORY, floor)));
+        }
+        assert!(library
+            .choose(RoomType::Boss, 5, &mut rng)
+            .unwrap()
+            .starts_with(&format!("{}/boss/3/", ROOM_DIRECTORY)));
+
+        // Rooms of deeper floors and rooms without weight are never chosen
+        let normal_rooms = library.groups[&RoomType::Normal].clone();
+        for name in normal_rooms.iter().skip(1) {
+            library.templates.get_mut(name).unwrap().weight = 0;
+        }
+        library
+            .templates
+            .get_mut(&normal_rooms[0])
+            .unwrap()
+            .min_floor = 2;
+
+        assert_eq!(
+            library.choose(RoomType::Normal, 1, &mut rng).unwrap_err(),
+            GenError::NoTemplate(RoomType::Normal, 1)
+        );
+        assert_eq!(
+            library.choose(RoomType::Normal, 2, &mut rng).unwrap(),
+            normal_rooms[0]
+        );
+        assert!(library.get(&normal_rooms[0]).is_ok());
+    }
+}
@@ -0,0 +1,238 @@
+// --- Imports ---
+use bevy::prelude::*;
+
+use crate::{
+    menu::AppState,
+    rng::GameRng,
+    spawnable::{
+        behavior::Health,
+        item::{spawn_item, ItemResource},
+        movement::check_collision,
+        player::Player,
+    },
+};
+
+use super::{calc_mid_room_pos, map_generation::RoomPos, TILE_SIZE};
+
+// --- Plugin declaration ---
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(pickup_heart.after("step3"))
+                .with_system(spawn_price_tags)
+                .with_system(despawn_price_tags),
+        );
+    }
+}
+
+// --- Constants ---
+/// Score points the player has to pay in a shop to restore one life
+pub const SHOP_HEALTH_PRICE: i32 = 300;
+/// Score points the player has to pay in a shop for an item
+pub const SHOP_ITEM_PRICE: i32 = 800;
+
+// --- Components ---
+/// Price of a pickup in a shop
+///
+/// The pickup is only collected if the player can pay the price, otherwise it stays in the shop.
+#[derive(Component)]
+pub struct ShopPrice {
+    pub price: i32,
+}
+
+/// Heart which restores one life when it is collected
+#[derive(Component)]
+pub struct HeartPickup;
+
+/// Text which shows the price of a pickup in a shop
+///
+/// * `offer` - The pickup with the price
+#[derive(Component)]
+pub struct PriceTag {
+    pub offer: Entity,
+}
+
+// --- Methods for Structs ---
+impl ShopPrice {
+    /// Let the player pay the price of a pickup
+    ///
+    /// # Arguments
+    /// * `price` - The price of the pickup, pickups without a price are free
+    /// * `player` - The player who pays
+    ///
+    /// # Returns
+    /// True if the pickup was free or the player paid it
+    pub fn pay(price: Option<&ShopPrice>, player: &mut Player) -> bool {
+        match price {
+            Some(price) if player.score < price.price => false,
+            Some(price) => {
+                player.score -= price.price;
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+// --- Functions ---
+/// Spawn the offers of a shop, a heart and a random item with their prices
+///
+/// The offers stay in the shop until they are bought, so the player can come back with more score.
+///
+/// # Arguments
+/// * `commands` - Commands to spawn the offers
+/// * `item_res` - The items to choose from
+/// * `room_pos` - Position of the shop
+/// * `rng` - The random number generator of the run
+pub fn spawn_shop_offers(
+    commands: &mut Commands,
+    item_res: &ItemResource,
+    room_pos: RoomPos,
+    rng: &mut GameRng,
+) {
+    let mid_pos = calc_mid_room_pos(room_pos.x, room_pos.y);
+
+    let heart = spawn_heart(
+        commands,
+        mid_pos - Vec2::new(2.0 * TILE_SIZE, 0.0),
+        room_pos,
+    );
+    commands.entity(heart).insert(ShopPrice {
+        price: SHOP_HEALTH_PRICE,
+    });
+
+    if let Some(item_type) = item_res.random_item(rng) {
+        let position = mid_pos + Vec2::new(2.0 * TILE_SIZE, 0.0);
+        if let Some(item) = spawn_item(commands, item_res, item_type, position, room_pos) {
+            commands.entity(item).insert(ShopPrice {
+                price: SHOP_ITEM_PRICE,
+            });
+        }
+    }
+}
+
+/// Spawn a heart pickup at the given position
+///
+/// # Returns
+/// The entity of the heart
+pub fn spawn_heart(commands: &mut Commands, position: Vec2, room_pos: RoomPos) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: position.extend(1.0),
+                scale: Vec3::new(20.0, 20.0, 0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: Color::rgb(1.0, 0.0, 0.2),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(HeartPickup)
+        .insert(Name::new("Heart"))
+        .insert(room_pos)
+        .id()
+}
+
+// --- System-Functions ---
+/// Restores one life of the player when a heart is collected
+///
+/// A player with full health doesn't collect the heart, so it can be bought later.
+fn pickup_heart(
+    mut commands: Commands,
+    heart_query: Query<(&Transform, Option<&ShopPrice>, Entity), With<HeartPickup>>,
+    mut player_query: Query<(&mut Player, &mut Health, &Transform)>,
+) {
+    let (mut player, mut health, player_transform) = player_query.single_mut();
+
+    for (heart_transform, price, entity) in heart_query.iter() {
+        if check_collision(player_transform, heart_transform)
+            && health.health < health.max_health
+            && ShopPrice::pay(price, &mut player)
+        {
+            health.health = f32::min(health.health + 1.0, health.max_health);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Shows the price above every new offer of a shop
+fn spawn_price_tags(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    offer_query: Query<(&ShopPrice, &Transform, &RoomPos, Entity), Added<ShopPrice>>,
+) {
+    for (price, transform, room_pos, offer) in offer_query.iter() {
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    price.price.to_string(),
+                    TextStyle {
+                        font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                        font_size: 30.0,
+                        color: Color::rgb(1.0, 0.8, 0.0),
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_translation(
+                    transform.translation.truncate().extend(2.0) + Vec3::new(0.0, TILE_SIZE, 0.0),
+                ),
+                ..Default::default()
+            })
+            .insert(PriceTag { offer })
+            .insert(Name::new("PriceTag"))
+            .insert(*room_pos);
+    }
+}
+
+/// Removes the prices of the offers which were bought
+fn despawn_price_tags(
+    mut commands: Commands,
+    tag_query: Query<(&PriceTag, Entity)>,
+    offer_query: Query<(), With<ShopPrice>>,
+) {
+    for (tag, entity) in tag_query.iter() {
+        if offer_query.get(tag.offer).is_err() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if the player only pays prices which the score covers
+    #[test]
+    fn test_pay_price() {
+        let mut player = Player {
+            score: SHOP_HEALTH_PRICE,
+            speed: 0.0,
+            color: Color::WHITE,
+        };
+
+        assert!(!ShopPrice::pay(
+            Some(&ShopPrice {
+                price: SHOP_ITEM_PRICE
+            }),
+            &mut player
+        ));
+        assert_eq!(player.score, SHOP_HEALTH_PRICE);
+
+        assert!(ShopPrice::pay(
+            Some(&ShopPrice {
+                price: SHOP_HEALTH_PRICE
+            }),
+            &mut player
+        ));
+        assert_eq!(player.score, 0);
+        assert!(ShopPrice::pay(None, &mut player));
+    }
+}
@@ -1,5 +1,13 @@
 use bevy::{app::AppExit, prelude::*};
 
+use crate::{
+    controls::{Action, ActionState},
+    highscore::RunStats,
+    map::{floor::Floor, room_library::RoomLibrary},
+    rng::GameRng,
+    save::{load_run, SaveError},
+};
+
 pub struct MenuPlugin;
 
 // --- Execute Systems ---
@@ -12,67 +20,124 @@ impl Plugin for MenuPlugin {
         )
         .add_event::<EndGameEvent>()
         .add_system_set(SystemSet::on_exit(AppState::MainMenu).with_system(cleanup))
-        .add_system_set(SystemSet::on_update(AppState::MainMenu).with_system(main_menu_controls))
-        .add_system_set(SystemSet::on_update(AppState::InGame).with_system(menu_on_escape));
+        .add_system_set(SystemSet::on_update(AppState::MainMenu).with_system(main_menu_controls));
     }
 }
 
 // --- Components and Structs ---
-/// Represents the current state of the game (main menu or in game)
+/// Represents the current state of the game
+///
+/// * `MainMenu` - The main menu is shown
+/// * `InGame` - A run is played
+/// * `Paused` - A run is paused, it is pushed on top of `InGame` so the run is kept
+/// * `HighScores` - The high-score table is shown
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
     MainMenu,
     InGame,
+    Paused,
+    HighScores,
 }
 
-/// Represents the state of the menu (represented by the current selection)
+/// Represents the state of the menu (represented by the current selection and the shown options)
 #[derive(Component)]
 pub struct MenuState {
     state: i32,
-    number_of_states: i32,
+    options: Vec<MenuOption>,
+}
+
+/// Options of the main menu
+///
+/// * `Play` - Starts a new run
+/// * `Continue` - Continues the saved run, only shown if there is a valid save
+/// * `HighScores` - Shows the high-score table
+/// * `Quit` - Exits the game
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuOption {
+    Play,
+    Continue,
+    HighScores,
+    Quit,
+}
+
+impl MenuOption {
+    /// Get the text of the option
+    fn text(&self) -> &str {
+        match self {
+            MenuOption::Play => "Play",
+            MenuOption::Continue => "Continue",
+            MenuOption::HighScores => "High Scores",
+            MenuOption::Quit => "Quit",
+        }
+    }
 }
 
 /// Marks the Menu-Options for querying
 #[derive(Component)]
 pub struct MenuOptions {}
 
-/// Event for showing the score at the end of the game
+/// Event for showing the score and the seed of the run at the end of the game
+///
+/// It also carries the details of the run, which are recorded in the high-score table
 pub struct EndGameEvent {
     pub score: i32,
     pub boss_slain: bool,
+    pub seed: u64,
+    pub floor: i32,
+    pub duration: f32,
+    pub kills: u32,
+    pub starting_weapon: String,
 }
 
-// --- System-Functions ---
-/// Goes back to the main menu on escape
-fn menu_on_escape(mut keys: ResMut<Input<KeyCode>>, mut app_state: ResMut<State<AppState>>) {
-    if keys.just_pressed(KeyCode::Escape) {
-        keys.reset(KeyCode::Escape);
-        app_state.set(AppState::MainMenu).unwrap();
+// --- Methods for Structs ---
+impl EndGameEvent {
+    /// Create the event for the end of a run
+    ///
+    /// # Arguments
+    /// * `score` - Score of the player
+    /// * `boss_slain` - If the run was won
+    /// * `seed` - Seed of the run
+    /// * `floor` - Floor the run ended on
+    /// * `stats` - Statistics of the run
+    pub fn new(score: i32, boss_slain: bool, seed: u64, floor: &Floor, stats: &RunStats) -> Self {
+        EndGameEvent {
+            score,
+            boss_slain,
+            seed,
+            floor: floor.number,
+            duration: stats.duration,
+            kills: stats.kills,
+            starting_weapon: stats.starting_weapon.clone(),
+        }
     }
 }
 
+// --- System-Functions ---
 /// Controls the main menu
 fn main_menu_controls(
-    keys: ResMut<Input<KeyCode>>,
+    mut commands: Commands,
+    mut actions: ResMut<ActionState>,
     mut app_state: ResMut<State<AppState>>,
     mut query_menu: Query<&mut MenuState>,
     mut query_menu_text: Query<&mut Text, With<MenuOptions>>,
     mut exit: EventWriter<AppExit>,
+    (mut rng, room_library): (ResMut<GameRng>, Res<RoomLibrary>),
 ) {
     if let Ok(mut menu_state) = query_menu.get_single_mut() {
         let mut menu_text = query_menu_text.single_mut();
 
-        //Adjust Menu State based on keys
-        if keys.any_just_pressed([KeyCode::Up, KeyCode::W]) {
+        //Adjust Menu State based on actions
+        if actions.just_pressed(Action::MenuUp) {
             menu_state.state += 1;
-        } else if keys.any_just_pressed([KeyCode::Down, KeyCode::S]) {
+        } else if actions.just_pressed(Action::MenuDown) {
             menu_state.state -= 1;
         }
 
         //Wrap around if end of list is reached
+        let number_of_states = menu_state.options.len() as i32;
         if menu_state.state < 0 {
-            menu_state.state = menu_state.number_of_states - 1;
-        } else if menu_state.state > menu_state.number_of_states - 1 {
+            menu_state.state = number_of_states - 1;
+        } else if menu_state.state > number_of_states - 1 {
             menu_state.state = 0;
         }
 
@@ -85,23 +150,40 @@ fn main_menu_controls(
             }
         }
 
-        // Exit the game on escape
-        if keys.just_pressed(KeyCode::Escape) {
+        // Exit the game on back
+        if actions.just_pressed(Action::Back) {
             exit.send(AppExit);
         }
 
-        //Start or exit the game on enter, depending on the menu state
-        if keys.just_pressed(KeyCode::Return) {
-            match menu_state.state {
-                0 => {
-                    println!("Start game");
+        //Start, continue or exit the game or show the high scores on confirm, depending on the menu state
+        if actions.just_pressed(Action::Confirm) {
+            actions.reset_just_pressed();
+            match menu_state.options[menu_state.state as usize] {
+                MenuOption::Play => {
+                    rng.start_run();
+                    println!("Start game with seed {}", rng.seed());
                     app_state.set(AppState::InGame).unwrap();
                 }
-                1 => {
-                    exit.send(AppExit);
+                MenuOption::Continue => match load_run().and_then(|save| {
+                    // The room files of the save could have been removed since it was written
+                    save.layout
+                        .check_templates(&room_library)
+                        .map_err(|err| SaveError::Format(err.to_string()))?;
+                    Ok(save)
+                }) {
+                    Ok(save) => {
+                        rng.resume_run(save.seed);
+                        println!("Continue game with seed {}", rng.seed());
+                        commands.insert_resource(save);
+                        app_state.set(AppState::InGame).unwrap();
+                    }
+                    Err(err) => println!("Error loading saved run: {}", err),
+                },
+                MenuOption::HighScores => {
+                    app_state.set(AppState::HighScores).unwrap();
                 }
-                _ => {
-                    println!("Error");
+                MenuOption::Quit => {
+                    exit.send(AppExit);
                 }
             }
         }
@@ -109,6 +191,8 @@ fn main_menu_controls(
 }
 
 /// Spawns the menu
+///
+/// The continue option is only shown if there is a valid saved run, an invalid save is shown with its error
 fn spawn_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -116,6 +200,17 @@ fn spawn_menu(
 ) {
     commands.spawn_bundle(UiCameraBundle::default());
 
+    let save = load_run();
+    let options = match save {
+        Ok(_) => vec![
+            MenuOption::Play,
+            MenuOption::Continue,
+            MenuOption::HighScores,
+            MenuOption::Quit,
+        ],
+        Err(_) => vec![MenuOption::Play, MenuOption::HighScores, MenuOption::Quit],
+    };
+
     commands
         .spawn_bundle(menu_wrapper())
         .with_children(|parent| {
@@ -133,19 +228,25 @@ fn spawn_menu(
                     &asset_server,
                     game_end.score,
                     game_end.boss_slain,
+                    game_end.seed,
                 ));
                 parent.spawn_bundle(spacer());
             }
         })
+        .with_children(|parent| {
+            if let Err(err) = &save {
+                if !matches!(err, SaveError::NotFound) {
+                    parent.spawn_bundle(menu_text_save_error(&asset_server, err));
+                    parent.spawn_bundle(spacer());
+                }
+            }
+        })
         .with_children(|parent| {
             parent
-                .spawn_bundle(menu_options_text(&asset_server))
+                .spawn_bundle(menu_options_text(&asset_server, &options))
                 .insert(MenuOptions {});
         })
-        .insert(MenuState {
-            state: 0,
-            number_of_states: 2,
-        });
+        .insert(MenuState { state: 0, options });
 }
 
 /// Despawns all entitys
@@ -212,27 +313,25 @@ fn logo(asset_server: &Res<AssetServer>) -> ImageBundle {
 }
 
 /// Menu-Options
-fn menu_options_text(asset_server: &Res<AssetServer>) -> TextBundle {
+fn menu_options_text(asset_server: &Res<AssetServer>, options: &[MenuOption]) -> TextBundle {
     TextBundle {
         text: Text {
-            sections: vec![
-                TextSection {
-                    value: "Play".to_string(),
-                    style: TextStyle {
-                        font: asset_server.load("fonts/orangeJuice2.0.ttf"),
-                        font_size: 40.0,
-                        color: Color::rgb(1.0, 1.0, 1.0),
+            sections: options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| TextSection {
+                    value: if i == 0 {
+                        option.text().to_string()
+                    } else {
+                        format!("\n{}", option.text())
                     },
-                },
-                TextSection {
-                    value: "\nQuit".to_string(),
                     style: TextStyle {
                         font: asset_server.load("fonts/orangeJuice2.0.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(1.0, 1.0, 1.0),
                     },
-                },
-            ],
+                })
+                .collect(),
             ..Default::default()
         },
         style: Style {
@@ -243,11 +342,12 @@ fn menu_options_text(asset_server: &Res<AssetServer>) -> TextBundle {
     }
 }
 
-/// Text after the game (score and boss slain)
+/// Text after the game (score, boss slain and seed)
 fn menu_text_after_game_end(
     asset_server: &Res<AssetServer>,
     score: i32,
     boss_slain: bool,
+    seed: u64,
 ) -> TextBundle {
     let score = &score.to_string();
 
@@ -256,7 +356,7 @@ fn menu_text_after_game_end(
     } else {
         "Game over!".to_string()
     };
-    let text = format!("{} \n Your score: {}", win_or_lose, score);
+    let text = format!("{} \n Your score: {} \n Seed: {}", win_or_lose, score, seed);
 
     TextBundle {
         text: Text {
@@ -278,6 +378,28 @@ fn menu_text_after_game_end(
     }
 }
 
+/// Text if the saved run couldn't be loaded
+fn menu_text_save_error(asset_server: &Res<AssetServer>, err: &SaveError) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: err.to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 30.0,
+                    color: Color::rgb(1.0, 0.3, 0.3),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
 /// Verticel line for spacing
 fn spacer() -> NodeBundle {
     NodeBundle {
@@ -0,0 +1,257 @@
+use bevy::{prelude::*, window::WindowMode};
+
+use crate::{
+    controls::{Action, ActionState},
+    menu::AppState,
+};
+
+pub struct PausePlugin;
+
+// --- Execute Systems ---
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_system_set(SystemSet::on_update(AppState::InGame).with_system(pause_on_escape))
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause_menu))
+            .add_system_set(SystemSet::on_update(AppState::Paused).with_system(pause_menu_controls))
+            .add_system_set(SystemSet::on_exit(AppState::Paused).with_system(despawn_pause_menu));
+    }
+}
+
+// --- Components and Structs ---
+/// Settings of the game which can be changed in the pause menu
+///
+/// * `fullscreen` - If the window is shown in fullscreen
+/// * `mouse_aim` - If the player shoots at the cursor with the shoot action
+#[derive(Default)]
+pub struct Settings {
+    pub fullscreen: bool,
+    pub mouse_aim: bool,
+}
+
+/// Page of the pause menu
+#[derive(Clone, Copy, PartialEq)]
+enum PausePage {
+    Main,
+    Settings,
+}
+
+/// Represents the state of the pause menu (represented by the current page and selection)
+#[derive(Component)]
+struct PauseMenu {
+    page: PausePage,
+    selection: usize,
+}
+
+/// Marks the options of the pause menu for querying
+#[derive(Component)]
+struct PauseOptions {}
+
+impl PauseMenu {
+    /// Get the options of the current page
+    fn options(&self, settings: &Settings) -> Vec<String> {
+        match self.page {
+            PausePage::Main => vec![
+                "Resume".to_string(),
+                "Settings".to_string(),
+                "Quit to Menu".to_string(),
+            ],
+            PausePage::Settings => vec![
+                format!(
+                    "Fullscreen: {}",
+                    if settings.fullscreen { "On" } else { "Off" }
+                ),
+                format!(
+                    "Mouse Aim: {}",
+                    if settings.mouse_aim { "On" } else { "Off" }
+                ),
+                "Back".to_string(),
+            ],
+        }
+    }
+
+    /// Open a page of the pause menu with the first option selected
+    fn open(&mut self, page: PausePage) {
+        self.page = page;
+        self.selection = 0;
+    }
+}
+
+// --- System-Functions ---
+/// Pauses the game on the pause action
+fn pause_on_escape(mut actions: ResMut<ActionState>, mut app_state: ResMut<State<AppState>>) {
+    if actions.just_pressed(Action::Pause) {
+        actions.reset_just_pressed();
+        app_state.push(AppState::Paused).unwrap();
+    }
+}
+
+/// Controls the pause menu
+///
+/// The game is resumed by popping the paused state, so the run stays untouched.
+/// Only quitting to the menu leaves the run and despawns it.
+fn pause_menu_controls(
+    mut actions: ResMut<ActionState>,
+    mut app_state: ResMut<State<AppState>>,
+    mut settings: ResMut<Settings>,
+    mut windows: ResMut<Windows>,
+    mut query_menu: Query<&mut PauseMenu>,
+    mut query_menu_text: Query<&mut Text, With<PauseOptions>>,
+) {
+    let mut menu = query_menu.single_mut();
+    let number_of_options = menu.options(&settings).len();
+
+    //Adjust selection based on actions and wrap around if end of list is reached
+    if actions.just_pressed(Action::MenuUp) {
+        menu.selection = (menu.selection + number_of_options - 1) % number_of_options;
+    } else if actions.just_pressed(Action::MenuDown) {
+        menu.selection = (menu.selection + 1) % number_of_options;
+    }
+
+    // Back goes back from the settings or resumes the game, pause always resumes it
+    if actions.just_pressed(Action::Back) {
+        actions.reset_just_pressed();
+        match menu.page {
+            PausePage::Main => app_state.pop().unwrap(),
+            PausePage::Settings => menu.open(PausePage::Main),
+        }
+    } else if actions.just_pressed(Action::Pause) {
+        actions.reset_just_pressed();
+        app_state.pop().unwrap();
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        actions.reset_just_pressed();
+        match (menu.page, menu.selection) {
+            (PausePage::Main, 0) => app_state.pop().unwrap(),
+            (PausePage::Main, 1) => menu.open(PausePage::Settings),
+            (PausePage::Main, _) => app_state.replace(AppState::MainMenu).unwrap(),
+            (PausePage::Settings, 0) => {
+                settings.fullscreen = !settings.fullscreen;
+                if let Some(window) = windows.get_primary_mut() {
+                    window.set_mode(if settings.fullscreen {
+                        WindowMode::BorderlessFullscreen
+                    } else {
+                        WindowMode::Windowed
+                    });
+                }
+            }
+            (PausePage::Settings, 1) => settings.mouse_aim = !settings.mouse_aim,
+            (PausePage::Settings, _) => menu.open(PausePage::Main),
+        }
+    }
+
+    //Show the options of the page and change the color for the selected option
+    let mut menu_text = query_menu_text.single_mut();
+    let font = menu_text.sections[0].style.font.clone();
+
+    menu_text.sections = menu
+        .options(&settings)
+        .into_iter()
+        .enumerate()
+        .map(|(i, option)| TextSection {
+            value: if i == 0 {
+                option
+            } else {
+                format!("\n{}", option)
+            },
+            style: TextStyle {
+                font: font.clone(),
+                font_size: 40.0,
+                color: if i == menu.selection {
+                    Color::RED
+                } else {
+                    Color::GRAY
+                },
+            },
+        })
+        .collect();
+}
+
+/// Spawns the pause menu over the game
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(pause_wrapper())
+        .with_children(|parent| {
+            parent.spawn_bundle(header(&asset_server));
+            parent
+                .spawn_bundle(pause_options_text(&asset_server))
+                .insert(PauseOptions {});
+        })
+        .insert(PauseMenu {
+            page: PausePage::Main,
+            selection: 0,
+        });
+}
+
+/// Despawns the pause menu when the game is resumed or quit
+fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// --- Ui-Elements ---
+/// Transparent wrapper for the pause menu, so the game stays visible behind it
+fn pause_wrapper() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        ..Default::default()
+    }
+}
+
+/// Header of the pause menu
+fn header(asset_server: &Res<AssetServer>) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: "Paused".to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 80.0,
+                    color: Color::rgb(1.0, 1.0, 1.0),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            margin: Rect {
+                bottom: Val::Px(40.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Options of the pause menu, the sections are set by the controls
+fn pause_options_text(asset_server: &Res<AssetServer>) -> TextBundle {
+    TextBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: String::new(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/orangeJuice2.0.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(1.0, 1.0, 1.0),
+                },
+            }],
+            ..Default::default()
+        },
+        style: Style {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
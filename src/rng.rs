@@ -0,0 +1,99 @@
+// --- Imports ---
+use rand::{rngs::StdRng, Error, RngCore, SeedableRng};
+use std::env;
+
+// --- Resources ---
+/// Shared random number generator of a run
+///
+/// Every random decision of the game draws from this resource, so a run can be reproduced by its seed.
+/// If a fixed seed is set, every new run starts with it, otherwise a new random seed is used for each run.
+pub struct GameRng {
+    seed: u64,
+    fixed_seed: Option<u64>,
+    rng: StdRng,
+}
+
+impl GameRng {
+    /// Create a new random number generator
+    ///
+    /// # Arguments
+    /// * `fixed_seed` - Seed which will be used for every run, a random seed is used if none is given
+    pub fn new(fixed_seed: Option<u64>) -> GameRng {
+        let seed = fixed_seed.unwrap_or_else(rand::random);
+
+        GameRng {
+            seed,
+            fixed_seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the seed of the current run
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Reseed the generator for a new run
+    ///
+    /// Uses the fixed seed if one is set, otherwise a new random seed
+    pub fn start_run(&mut self) {
+        self.seed = self.fixed_seed.unwrap_or_else(rand::random);
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    /// Reseed the generator to continue a saved run
+    ///
+    /// The generator starts again from the seed of the run, so random decisions after continuing
+    /// differ from the ones the run would have made without saving
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the saved run
+    pub fn resume_run(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(None)
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+// --- Functions ---
+/// Read the seed from the command line arguments
+///
+/// The seed is passed with `--seed <number>`
+///
+/// # Returns
+/// The seed if one was passed and is a valid number
+pub fn seed_from_args() -> Option<u64> {
+    let mut args = env::args().skip_while(|arg| arg != "--seed").skip(1);
+
+    match args.next().map(|seed| seed.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(err)) => {
+            println!("Invalid seed, using a random one: {}", err);
+            None
+        }
+        None => None,
+    }
+}
@@ -0,0 +1,467 @@
+// --- Imports ---
+use bevy::prelude::*;
+use ron::{
+    de::from_bytes,
+    ser::{to_string_pretty, PrettyConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use crate::{
+    highscore::RunStats,
+    map::{
+        floor::Floor,
+        map_generation::{MapLayout, RoomPos},
+        room::{Room, RoomStatus},
+        room_generation::Spawner,
+        shop::{spawn_heart, HeartPickup, ShopPrice},
+    },
+    menu::{AppState, EndGameEvent},
+    rng::GameRng,
+    spawnable::{
+        behavior::Health,
+        enemy::{
+            behavior::{BehaviorState, Enemy},
+            boss::BossFight,
+            enemy_types::EnemyType,
+        },
+        item::{spawn_item, ItemList, ItemPickup, ItemResource, ItemType},
+        player::Player,
+        weapon::{spawn_weapon_pickup, WeaponList, WeaponPickup, WeaponResource, WeaponTypes},
+    },
+};
+
+// --- Plugin declaration ---
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DiscardRunEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(finish_resume))
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(save_run));
+    }
+}
+
+// --- Constants ---
+/// Version of the save format, saves with another version are rejected
+pub const SAVE_VERSION: u32 = 1;
+/// Path of the saved run
+pub const SAVE_PATH: &str = "saves/run.ron";
+
+// --- Resources ---
+/// Saved run which can be continued from the main menu
+///
+/// If it is inserted as a resource when the game starts, the saved run is spawned instead of a new one.
+///
+/// * `version` - Version of the save format
+/// * `seed` - Seed of the run
+/// * `floor` - Number of the current floor
+/// * `layout` - Generated map and room templates of the current floor
+/// * `rooms` - Status of every room of the current floor
+/// * `spawners` - Spawners which didn't spawn their enemy yet and the living enemies
+/// * `pickups` - Pickups which lie on the floor
+/// * `player` - State of the player
+/// * `stats` - Statistics of the run for the high-score table
+#[derive(Serialize, Deserialize)]
+pub struct RunSave {
+    pub version: u32,
+    pub seed: u64,
+    pub floor: i32,
+    pub layout: MapLayout,
+    pub rooms: HashMap<RoomPos, RoomStatus>,
+    pub spawners: Vec<SpawnerSave>,
+    #[serde(default)]
+    pub pickups: Vec<PickupSave>,
+    pub player: PlayerSave,
+    #[serde(default)]
+    pub stats: RunStats,
+}
+
+/// Saved spawner
+///
+/// * `state` - The state of a living enemy, spawners which didn't spawn their enemy yet have none
+#[derive(Serialize, Deserialize)]
+pub struct SpawnerSave {
+    pub position: Vec2,
+    pub room_pos: RoomPos,
+    pub enemy_type: EnemyType,
+    #[serde(default)]
+    pub state: Option<EnemyState>,
+}
+
+/// Saved state of a living enemy
+///
+/// * `health` - Health of the enemy
+/// * `boss_phase` - Current phase of a boss
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EnemyState {
+    pub health: Health,
+    #[serde(default)]
+    pub boss_phase: Option<usize>,
+}
+
+/// Saved pickup
+///
+/// * `price` - Price of a pickup in a shop
+#[derive(Serialize, Deserialize)]
+pub struct PickupSave {
+    pub position: Vec2,
+    pub room_pos: RoomPos,
+    pub pickup: Pickup,
+    #[serde(default)]
+    pub price: Option<i32>,
+}
+
+/// Kind of a saved pickup
+///
+/// * `Item` - Item pickup of the item type
+/// * `Weapon` - Weapon pickup of the weapon type
+/// * `Heart` - Heart which restores one life
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum Pickup {
+    Item(ItemType),
+    Weapon(WeaponTypes),
+    Heart,
+}
+
+/// Saved state of the player
+#[derive(Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub position: Vec2,
+    pub score: i32,
+    pub speed: f32,
+    pub health: Health,
+    pub weapons: WeaponList,
+    pub items: Vec<ItemType>,
+}
+
+/// Event to discard the run when it returns to the main menu, because its map couldn't be generated or spawned
+///
+/// The save is deleted instead of written, so a broken floor can't be continued.
+pub struct DiscardRunEvent;
+
+/// Version of a save, it is read first so saves with another format are rejected with a readable error
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+/// Error while loading or writing a save
+///
+/// * `NotFound` - There is no saved run
+/// * `Io` - The save file couldn't be read or written
+/// * `Format` - The save file isn't a valid save
+/// * `Version` - The save was written by another version of the game
+#[derive(Debug)]
+pub enum SaveError {
+    NotFound,
+    Io(io::Error),
+    Format(String),
+    Version(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::NotFound => write!(f, "No saved run found"),
+            SaveError::Io(err) => write!(f, "Could not access the saved run: {}", err),
+            SaveError::Format(err) => write!(f, "Saved run is invalid: {}", err),
+            SaveError::Version(version) => write!(
+                f,
+                "Saved run has version {}, but version {} is required",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+// --- Type aliases ---
+/// Query of the living enemies with the state which is saved
+type EnemyStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static EnemyType,
+        &'static BehaviorState,
+        &'static Health,
+        Option<&'static BossFight>,
+        &'static RoomPos,
+    ),
+    With<Enemy>,
+>;
+
+/// Query of the pickups of a kind with their price in a shop
+type PickupQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        &'static T,
+        &'static Transform,
+        &'static RoomPos,
+        Option<&'static ShopPrice>,
+    ),
+>;
+
+// --- Systems ---
+/// Removes the saved run after it was spawned, so the next game starts a new run
+fn finish_resume(mut commands: Commands) {
+    commands.remove_resource::<RunSave>();
+}
+
+/// Saves the run when the player quits to the main menu
+///
+/// A finished run can't be continued, so its save is deleted instead.
+/// The save is also deleted if the map of the run couldn't be generated or spawned.
+/// Living enemies are saved as spawners at their origin with their health and boss phase,
+/// so their fight continues when the run is continued.
+fn save_run(
+    (mut ev_game_end, mut ev_discard_run): (
+        EventReader<EndGameEvent>,
+        EventReader<DiscardRunEvent>,
+    ),
+    (rng, floor, layout, stats): (Res<GameRng>, Res<Floor>, Res<MapLayout>, Res<RunStats>),
+    room_query: Query<(&RoomPos, &RoomStatus), With<Room>>,
+    spawner_query: Query<(&Transform, &Spawner, &RoomPos)>,
+    enemy_query: EnemyStateQuery,
+    (item_query, weapon_query, heart_query): (
+        PickupQuery<ItemPickup>,
+        PickupQuery<WeaponPickup>,
+        PickupQuery<HeartPickup>,
+    ),
+    player_query: Query<(&Player, &Health, &WeaponList, &ItemList, &Transform)>,
+) {
+    if ev_game_end.iter().next().is_some() || ev_discard_run.iter().next().is_some() {
+        delete_run();
+        return;
+    }
+
+    let (player, health, weapons, items, transform) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let mut spawners: Vec<SpawnerSave> = spawner_query
+        .iter()
+        .map(|(transform, spawner, room_pos)| SpawnerSave {
+            position: transform.translation.truncate(),
+            room_pos: *room_pos,
+            enemy_type: spawner.enemy_type.clone(),
+            state: spawner.state.clone(),
+        })
+        .collect();
+    spawners.extend(enemy_query.iter().map(
+        |(enemy_type, behavior_state, health, fight, room_pos)| SpawnerSave {
+            position: behavior_state.origin,
+            room_pos: *room_pos,
+            enemy_type: enemy_type.clone(),
+            state: Some(EnemyState {
+                health: health.clone(),
+                boss_phase: fight.and_then(|fight| fight.phase),
+            }),
+        },
+    ));
+
+    let pickup_save =
+        |pickup, transform: &Transform, room_pos: &RoomPos, price: Option<&ShopPrice>| PickupSave {
+            position: transform.translation.truncate(),
+            room_pos: *room_pos,
+            pickup,
+            price: price.map(|price| price.price),
+        };
+    let mut pickups: Vec<PickupSave> = item_query
+        .iter()
+        .map(|(item, transform, room_pos, price)| {
+            pickup_save(
+                Pickup::Item(item.item_type.clone()),
+                transform,
+                room_pos,
+                price,
+            )
+        })
+        .collect();
+    pickups.extend(
+        weapon_query
+            .iter()
+            .map(|(weapon, transform, room_pos, price)| {
+                pickup_save(
+                    Pickup::Weapon(weapon.weapon_type.clone()),
+                    transform,
+                    room_pos,
+                    price,
+                )
+            }),
+    );
+    pickups.extend(heart_query.iter().map(|(_, transform, room_pos, price)| {
+        pickup_save(Pickup::Heart, transform, room_pos, price)
+    }));
+
+    let save = RunSave {
+        version: SAVE_VERSION,
+        seed: rng.seed(),
+        floor: floor.number,
+        layout: layout.clone(),
+        rooms: room_query
+            .iter()
+            .map(|(room_pos, room_status)| (*room_pos, room_status.clone()))
+            .collect(),
+        spawners,
+        pickups,
+        player: PlayerSave {
+            position: transform.translation.truncate(),
+            score: player.score,
+            speed: player.speed,
+            health: health.clone(),
+            weapons: weapons.clone(),
+            items: items.items.clone(),
+        },
+        stats: stats.clone(),
+    };
+
+    match write_run(Path::new(SAVE_PATH), &save) {
+        Ok(()) => println!("Saved run to {}", SAVE_PATH),
+        Err(err) => println!("Error saving run: {}", err),
+    }
+}
+
+// --- Functions ---
+/// Load the saved run
+///
+/// # Returns
+/// The saved run, or an error if there is no valid save with the current version
+pub fn load_run() -> Result<RunSave, SaveError> {
+    load_run_from(Path::new(SAVE_PATH))
+}
+
+/// Load a run from a save file
+///
+/// # Arguments
+/// * `path` - Path of the save file
+fn load_run_from(path: &Path) -> Result<RunSave, SaveError> {
+    let bytes = fs::read(path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => SaveError::NotFound,
+        _ => SaveError::Io(err),
+    })?;
+
+    // Check the version before the whole save is parsed, because the format of other versions can differ
+    let version = from_bytes::<SaveVersion>(&bytes)
+        .map_err(|err| SaveError::Format(err.to_string()))?
+        .version;
+    if version != SAVE_VERSION {
+        return Err(SaveError::Version(version));
+    }
+
+    from_bytes::<RunSave>(&bytes).map_err(|err| SaveError::Format(err.to_string()))
+}
+
+/// Write a run to a save file
+///
+/// # Arguments
+/// * `path` - Path of the save file
+/// * `save` - The run to save
+fn write_run(path: &Path, save: &RunSave) -> Result<(), SaveError> {
+    let text = to_string_pretty(save, PrettyConfig::default())
+        .map_err(|err| SaveError::Format(err.to_string()))?;
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(SaveError::Io)?;
+    }
+
+    fs::write(path, text).map_err(SaveError::Io)
+}
+
+/// Spawn the saved pickups of a run
+///
+/// # Arguments
+/// * `commands` - Commands to spawn the pickups
+/// * `pickups` - The saved pickups
+/// * `item_res` - The items of the item pickups
+/// * `weapon_res` - The weapons of the weapon pickups
+pub fn restore_pickups(
+    commands: &mut Commands,
+    pickups: &[PickupSave],
+    item_res: &ItemResource,
+    weapon_res: &WeaponResource,
+) {
+    for pickup in pickups.iter() {
+        let entity = match &pickup.pickup {
+            Pickup::Item(item_type) => spawn_item(
+                commands,
+                item_res,
+                item_type.clone(),
+                pickup.position,
+                pickup.room_pos,
+            ),
+            Pickup::Weapon(weapon_type) => Some(spawn_weapon_pickup(
+                commands,
+                weapon_res,
+                weapon_type.clone(),
+                pickup.position,
+                pickup.room_pos,
+            )),
+            Pickup::Heart => Some(spawn_heart(commands, pickup.position, pickup.room_pos)),
+        };
+
+        if let (Some(entity), Some(price)) = (entity, pickup.price) {
+            commands.entity(entity).insert(ShopPrice { price });
+        }
+    }
+}
+
+/// Delete the save file if there is one
+fn delete_run() {
+    if let Err(err) = fs::remove_file(SAVE_PATH) {
+        if err.kind() != io::ErrorKind::NotFound {
+            println!("Error deleting saved run: {}", err);
+        }
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+    use std::env;
+
+    /// Test if a save of another version is rejected with a readable error
+    #[test]
+    fn test_load_other_version() {
+        let path = env::temp_dir().join("ruspect_test_version.ron");
+        let save = RunSave {
+            version: SAVE_VERSION + 1,
+            seed: 0,
+            floor: 1,
+            layout: MapLayout::default(),
+            rooms: HashMap::new(),
+            spawners: vec![],
+            pickups: vec![],
+            player: PlayerSave {
+                position: Vec2::ZERO,
+                score: 0,
+                speed: 0.0,
+                health: Health {
+                    health: 3.0,
+                    max_health: 3.0,
+                },
+                weapons: WeaponList {
+                    weapons: vec![],
+                    active: 0,
+                },
+                items: vec![],
+            },
+            stats: RunStats::default(),
+        };
+        write_run(&path, &save).unwrap();
+
+        let err = load_run_from(&path).err();
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(err, Some(SaveError::Version(version)) if version == SAVE_VERSION + 1));
+        assert_eq!(
+            err.unwrap().to_string(),
+            format!(
+                "Saved run has version {}, but version {} is required",
+                SAVE_VERSION + 1,
+                SAVE_VERSION
+            )
+        );
+    }
+}
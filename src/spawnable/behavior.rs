@@ -1,9 +1,15 @@
 use super::{enemy::behavior::EnemySlainEvent, player::DEFAULT_INVINCIBILITY_DURATION};
 use crate::{
-    map::map_generation::RoomPos,
+    highscore::RunStats,
+    map::{
+        floor::{spawn_trapdoor, Floor},
+        map_generation::RoomPos,
+    },
     menu::{AppState, EndGameEvent},
+    rng::GameRng,
     spawnable::{
         bullet::Bullet,
+        collision::CollisionEvent,
         enemy::{
             behavior::Enemy, behavior::Hitstun, enemy_types::spawn_enemy_type,
             enemy_types::EnemyResource, enemy_types::EnemyType,
@@ -13,12 +19,14 @@ use crate::{
         },
         player::Invincibility,
         player::Player,
-        weapon::WeaponResource,
+        status_effect::{StatusEffect, StatusEffects},
+        weapon::{spawn_weapon_pickup, WeaponResource},
     },
     TIME_STEP,
 };
 use bevy::{core::FixedTimestep, prelude::*};
-use serde::Deserialize;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 pub struct SpawnablePlugin;
 
@@ -37,7 +45,9 @@ impl Plugin for SpawnablePlugin {
                 .with_system(check_spawnable_behavior.label("despawn").before("step1")),
         )
         .add_system_set(
-            SystemSet::on_update(AppState::InGame).with_system(check_damage.after("step3")),
+            SystemSet::on_update(AppState::InGame)
+                .with_system(collision_damage.after("step2").before("step3"))
+                .with_system(check_damage.after("step3")),
         );
     }
 }
@@ -58,6 +68,8 @@ pub enum DespawnBehavior {
     SpawnNewMob(EnemyType),
     Despawn,
     EndGame,
+    NextFloor,
+    DropWeapon(f32),
 }
 
 /// Event for taking damage
@@ -67,7 +79,7 @@ pub struct TakeDamageEvent {
 }
 
 /// Health component for enemies and player
-#[derive(Component, Deserialize, Clone)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Health {
     pub health: f32,
     pub max_health: f32,
@@ -96,6 +108,7 @@ fn check_health(
     >,
     mut app_state: ResMut<State<AppState>>,
     mut ev_game_end: EventWriter<EndGameEvent>,
+    (rng, floor, stats): (Res<GameRng>, Res<Floor>, Res<RunStats>),
 ) {
     for (health, mut spawnable, mut sprite, player, enemy) in health_query.iter_mut() {
         if enemy.is_some() {
@@ -105,10 +118,13 @@ fn check_health(
             }
         } else if player.is_some() && health.health <= 0.0 {
             app_state.set(AppState::MainMenu).unwrap();
-            ev_game_end.send(EndGameEvent {
-                score: player.unwrap().score,
-                boss_slain: false,
-            });
+            ev_game_end.send(EndGameEvent::new(
+                player.unwrap().score,
+                false,
+                rng.seed(),
+                &floor,
+                &stats,
+            ));
         }
     }
 }
@@ -130,6 +146,8 @@ fn check_spawnable_behavior(
     mut ev_enemy_slain: EventWriter<EnemySlainEvent>,
     mut ev_game_end: EventWriter<EndGameEvent>,
     mut app_state: ResMut<State<AppState>>,
+    mut rng: ResMut<GameRng>,
+    (floor, stats): (Res<Floor>, Res<RunStats>),
 ) {
     for (spawnable, entity, transform, room_pos, health_option) in spawnables.iter() {
         let mut player = player_query.single_mut();
@@ -161,6 +179,7 @@ fn check_spawnable_behavior(
                                 &mob_type,
                                 Vec2::new(transform.translation.x, transform.translation.y),
                                 room_pos,
+                                None,
                             );
                             spawned_enemy = true;
                         }
@@ -168,13 +187,46 @@ fn check_spawnable_behavior(
                     DespawnBehavior::Despawn => {
                         commands.entity(entity).despawn();
                     }
+                    DespawnBehavior::DropWeapon(chance) => {
+                        if let Some(&room_pos) = room_pos {
+                            if rng.gen::<f32>() < chance {
+                                spawn_weapon_pickup(
+                                    &mut commands,
+                                    &weapon_res,
+                                    rng.gen(),
+                                    Vec2::new(transform.translation.x, transform.translation.y),
+                                    room_pos,
+                                );
+                            }
+                        }
+                    }
                     DespawnBehavior::EndGame => {
-                        ev_game_end.send(EndGameEvent {
-                            score: player.score,
-                            boss_slain: true,
-                        });
+                        ev_game_end.send(EndGameEvent::new(
+                            player.score,
+                            true,
+                            rng.seed(),
+                            &floor,
+                            &stats,
+                        ));
                         app_state.set(AppState::MainMenu).unwrap();
                     }
+                    DespawnBehavior::NextFloor => {
+                        // Open the trapdoor to the next floor or end the game on the final floor
+                        if !floor.is_final() {
+                            if let Some(&room_pos) = room_pos {
+                                spawn_trapdoor(&mut commands, room_pos);
+                            }
+                        } else {
+                            ev_game_end.send(EndGameEvent::new(
+                                player.score,
+                                true,
+                                rng.seed(),
+                                &floor,
+                                &stats,
+                            ));
+                            app_state.set(AppState::MainMenu).unwrap();
+                        }
+                    }
                 }
             }
         }
@@ -186,6 +238,19 @@ fn check_spawnable_behavior(
     }
 }
 
+/// Sends a TakeDamageEvent for every collision which damages an entity
+fn collision_damage(
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut take_damage: EventWriter<TakeDamageEvent>,
+) {
+    for (entity, damage_entity) in ev_collision.iter().filter_map(|ev| ev.damage()) {
+        take_damage.send(TakeDamageEvent {
+            entity,
+            damage_entity,
+        });
+    }
+}
+
 /// Iterates over the TakeDamageEvent and executes necessary actions
 fn check_damage(
     mut take_damage: EventReader<TakeDamageEvent>,
@@ -197,6 +262,7 @@ fn check_damage(
         Option<&Player>,
         Option<&Invincibility>,
         Option<&Enemy>,
+        Option<&mut StatusEffects>,
     )>,
     mut damage_entity: Query<(&Transform, Option<&Bullet>, Option<&Enemy>)>,
     mut commands: Commands,
@@ -210,6 +276,7 @@ fn check_damage(
             player_option,
             invincibility,
             enemy_option,
+            status_effects,
         )) = health_entity.get_mut(take_damage.entity)
         {
             if let Ok((damage_transform, damage_bullet_option, damage_enemy_option)) =
@@ -217,17 +284,29 @@ fn check_damage(
             {
                 //Get damage and despawn if bullet
                 let mut damage = 0.0;
+                let mut effects: &[StatusEffect] = &[];
 
                 match (damage_bullet_option, damage_enemy_option) {
                     (Some(damage_bullet_option), None) => {
                         damage = damage_bullet_option.damage;
+                        effects = &damage_bullet_option.effects;
                     }
                     (None, Some(damage_enemy_option)) => {
                         damage = damage_enemy_option.damage;
+                        effects = &damage_enemy_option.contact_effects;
                     }
                     _ => {}
                 }
 
+                // Status effects are applied like the damage, so an invincible player isn't affected
+                if let Some(mut status_effects) = status_effects {
+                    if player_option.is_none() || invincibility.is_none() {
+                        for effect in effects {
+                            status_effects.apply(effect);
+                        }
+                    }
+                }
+
                 // Insert invincibility if player is hit and hitstun for the enemy
                 if player_option.is_some() {
                     if invincibility.is_none() {
@@ -1,6 +1,9 @@
 use crate::{
     menu::AppState,
-    spawnable::{behavior::Spawnable, movement::Movement},
+    spawnable::{
+        behavior::Spawnable, collision::CollisionResponse, enemy::behavior::Enemy,
+        movement::Movement, player::Player, status_effect::StatusEffect, weapon::rotate_clockwise,
+    },
 };
 use bevy::prelude::*;
 
@@ -19,6 +22,17 @@ impl Plugin for BulletPlugin {
 
 // --- Components and Structs ---
 /// Bullet component
+///
+/// The projectile modifiers of the weapon are counted down while the bullet flies
+///
+/// * `pierce` - Remaining targets the bullet passes through
+/// * `bounce` - Remaining bounces off walls
+/// * `homing` - Strength of the turn toward the nearest target in radians per second
+/// * `explosion_radius` - Radius of the explosion when the bullet stops, 0 for no explosion
+/// * `lifetime` - Remaining time in seconds until the bullet stops, which is counted down by the frame time
+///   and not per frame. None if the bullet flies until it hits something
+/// * `hits` - Entities which were hit by the bullet, so a piercing bullet hits every target only once
+/// * `effects` - Status effects which are applied to the targets
 #[derive(Component)]
 pub struct Bullet {
     pub speed: f32,
@@ -28,21 +42,209 @@ pub struct Bullet {
     pub knockback_strength: f32,
     pub knockback_duration: f32,
     pub hitstun_duration: f32,
+    pub pierce: u32,
+    pub bounce: u32,
+    pub homing: f32,
+    pub explosion_radius: f32,
+    pub lifetime: Option<f32>,
+    pub hits: Vec<Entity>,
+    pub effects: Vec<StatusEffect>,
+}
+
+/// What a bullet does after a collision
+///
+/// * `Pierce` - The bullet flies through the target
+/// * `Bounce` - The bullet is reflected by the wall
+/// * `Stop` - The bullet stops, explodes if it is explosive and is despawned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulletImpact {
+    Pierce,
+    Bounce,
+    Stop,
+}
+
+// --- Methods for Structs ---
+impl Bullet {
+    /// Check if the lifetime of the bullet is over
+    pub fn is_expired(&self) -> bool {
+        matches!(self.lifetime, Some(lifetime) if lifetime <= 0.0)
+    }
+
+    /// Use up a pierce or a bounce for a collision
+    ///
+    /// # Arguments
+    /// * `response` - Response of the collision
+    /// * `other` - The entity the bullet collided with
+    ///
+    /// # Returns
+    /// What the bullet does after the collision
+    pub fn impact(&mut self, response: CollisionResponse, other: Entity) -> BulletImpact {
+        match response {
+            CollisionResponse::Damage if self.pierce > 0 => {
+                self.pierce -= 1;
+                self.hits.push(other);
+                BulletImpact::Pierce
+            }
+            CollisionResponse::Block if self.bounce > 0 => {
+                self.bounce -= 1;
+                BulletImpact::Bounce
+            }
+            _ => BulletImpact::Stop,
+        }
+    }
 }
 
 // --- System-Functions ---
-// Adds movement to bullets
+/// Adds movement to bullets
+///
+/// Homing bullets turn toward the nearest target and the lifetime of the bullets is counted down.
+/// Expired bullets are stopped by the collision system, so they can still explode.
 fn bullet_movement_and_collision(
-    entity_query: Query<(&Bullet, Entity, &Transform, &Spawnable), With<Bullet>>,
+    time: Res<Time>,
+    mut entity_query: Query<(&mut Bullet, Entity, &Transform, &Spawnable), With<Bullet>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Bullet>)>,
+    player_query: Query<&Transform, (With<Player>, Without<Bullet>)>,
     mut commands: Commands,
 ) {
-    for (bullet, bullet_entity, transform, spawnable) in entity_query.iter() {
-        if !(spawnable.despawn || bullet.direction.x == 0.0 && bullet.direction.y == 0.0) {
-            commands.entity(bullet_entity).insert(Movement {
-                direction: Vec2::new(bullet.direction.x, bullet.direction.y).normalize(),
-                transform: *transform,
-                speed: bullet.speed,
-            });
+    for (mut bullet, bullet_entity, transform, spawnable) in entity_query.iter_mut() {
+        if spawnable.despawn || bullet.direction.x == 0.0 && bullet.direction.y == 0.0 {
+            continue;
+        }
+
+        if let Some(lifetime) = bullet.lifetime.as_mut() {
+            *lifetime -= time.delta_seconds();
         }
+
+        if bullet.homing > 0.0 {
+            let position = transform.translation.truncate();
+            let target = if bullet.is_player {
+                enemy_query
+                    .iter()
+                    .map(|transform| transform.translation.truncate())
+                    .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+            } else {
+                player_query
+                    .get_single()
+                    .ok()
+                    .map(|transform| transform.translation.truncate())
+            };
+
+            if let Some(target) = target {
+                bullet.direction = turn_toward(
+                    bullet.direction.normalize(),
+                    (target - position).normalize_or_zero(),
+                    bullet.homing * time.delta_seconds(),
+                );
+            }
+        }
+
+        commands.entity(bullet_entity).insert(Movement {
+            direction: Vec2::new(bullet.direction.x, bullet.direction.y).normalize(),
+            transform: *transform,
+            speed: bullet.speed,
+        });
+    }
+}
+
+// --- Functions ---
+/// Turns a direction toward a target direction by at most the given angle
+///
+/// # Arguments
+/// * `direction` - The current direction
+/// * `target` - The direction to the target
+/// * `max_angle` - The maximum angle of the turn in radians
+fn turn_toward(direction: Vec2, target: Vec2, max_angle: f32) -> Vec2 {
+    if target == Vec2::ZERO {
+        return direction;
+    }
+
+    // Angle from the direction to the target, clockwise is positive like in the shooting patterns
+    let angle = -direction.angle_between(target);
+    rotate_clockwise(direction, angle.clamp(-max_angle, max_angle))
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Create a bullet with the given pierces and bounces
+    #[allow(dead_code)]
+    fn bullet(pierce: u32, bounce: u32) -> Bullet {
+        Bullet {
+            speed: 1.0,
+            direction: Vec2::X,
+            damage: 1.0,
+            is_player: true,
+            knockback_strength: 0.0,
+            knockback_duration: 0.0,
+            hitstun_duration: 0.0,
+            pierce,
+            bounce,
+            homing: 0.0,
+            explosion_radius: 0.0,
+            lifetime: None,
+            hits: vec![],
+            effects: vec![],
+        }
+    }
+
+    /// Test if a bullet pierces its targets until its pierces are used up
+    #[test]
+    fn test_impact_pierce() {
+        let mut bullet = bullet(1, 0);
+        let (first, second) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        assert_eq!(
+            bullet.impact(CollisionResponse::Damage, first),
+            BulletImpact::Pierce
+        );
+        assert_eq!(bullet.pierce, 0);
+        assert_eq!(bullet.hits, vec![first]);
+
+        assert_eq!(
+            bullet.impact(CollisionResponse::Damage, second),
+            BulletImpact::Stop
+        );
+        assert_eq!(bullet.hits, vec![first]);
+    }
+
+    /// Test if a bullet bounces off walls until its bounces are used up and pierces don't bounce
+    #[test]
+    fn test_impact_bounce() {
+        let mut bullet = bullet(1, 1);
+        let wall = Entity::from_raw(1);
+
+        assert_eq!(
+            bullet.impact(CollisionResponse::Block, wall),
+            BulletImpact::Bounce
+        );
+        assert_eq!(bullet.bounce, 0);
+        assert_eq!(
+            bullet.impact(CollisionResponse::Block, wall),
+            BulletImpact::Stop
+        );
+        assert_eq!(bullet.pierce, 1);
+        assert!(bullet.hits.is_empty());
+    }
+
+    /// Test if a homing bullet turns by at most the given angle in the direction of the target
+    #[test]
+    fn test_turn_toward() {
+        let max_angle = 0.1;
+
+        // The target above is turned to counterclockwise, the target below clockwise
+        let turned = turn_toward(Vec2::X, Vec2::Y, max_angle);
+        assert!(turned.abs_diff_eq(Vec2::new(max_angle.cos(), max_angle.sin()), 1e-5));
+        let turned = turn_toward(Vec2::X, -Vec2::Y, max_angle);
+        assert!(turned.abs_diff_eq(Vec2::new(max_angle.cos(), -max_angle.sin()), 1e-5));
+
+        // A close target is reached without turning past it
+        let target = rotate_clockwise(Vec2::X, -max_angle / 2.0);
+        assert!(turn_toward(Vec2::X, target, max_angle).abs_diff_eq(target, 1e-5));
+        assert!(turn_toward(Vec2::X, Vec2::Y, PI).abs_diff_eq(Vec2::Y, 1e-5));
+
+        // Without a target the direction stays the same
+        assert_eq!(turn_toward(Vec2::X, Vec2::ZERO, max_angle), Vec2::X);
     }
 }
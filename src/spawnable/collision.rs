@@ -0,0 +1,142 @@
+// --- Imports ---
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::movement::Collider;
+
+// --- Structs ---
+/// Response of a collision between a moving entity and another entity
+///
+/// * `Ignore` - The entities pass through each other
+/// * `Block` - The moving entity is stopped
+/// * `Damage` - The moving entity damages the other entity and is stopped
+/// * `TakeDamage` - The moving entity is damaged by the other entity and is stopped
+/// * `Trigger` - The collision triggers the event of the other entity and the moving entity is stopped
+///
+/// Bullets are despawned instead of stopped.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionResponse {
+    Ignore,
+    Block,
+    Damage,
+    TakeDamage,
+    Trigger,
+}
+
+// --- Resources ---
+/// Collision matrix for loading the collision responses from ron file
+///
+/// The responses are declared for pairs of the collider of the moving entity and the collider of the other entity,
+/// pairs which aren't declared are ignored.
+#[derive(Deserialize, Clone)]
+pub struct CollisionMatrix {
+    responses: HashMap<(Collider, Collider), CollisionResponse>,
+}
+
+impl CollisionMatrix {
+    /// Get the response of a collision
+    ///
+    /// # Arguments
+    /// * `collider` - The collider of the moving entity
+    /// * `other` - The collider of the other entity
+    pub fn get(&self, collider: Collider, other: Collider) -> CollisionResponse {
+        self.responses
+            .get(&(collider, other))
+            .copied()
+            .unwrap_or(CollisionResponse::Ignore)
+    }
+}
+
+// --- Events ---
+/// Event for a collision which isn't ignored
+///
+/// * `entity` - The moving entity
+/// * `other` - The entity it collided with
+/// * `colliders` - The colliders of the moving and the other entity
+/// * `response` - The response of the collision matrix
+pub struct CollisionEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    pub colliders: (Collider, Collider),
+    pub response: CollisionResponse,
+}
+
+impl CollisionEvent {
+    /// Get the damaged entity and the entity which deals the damage
+    ///
+    /// # Returns
+    /// The damaged and the damaging entity, or `None` if the collision doesn't damage
+    pub fn damage(&self) -> Option<(Entity, Entity)> {
+        match self.response {
+            CollisionResponse::Damage => Some((self.other, self.entity)),
+            CollisionResponse::TakeDamage => Some((self.entity, self.other)),
+            _ => None,
+        }
+    }
+
+    /// Check if the collision triggers the event of an entity with the given collider
+    pub fn triggers(&self, collider: Collider) -> bool {
+        self.response == CollisionResponse::Trigger && self.colliders.1 == collider
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+    use ron::de::from_bytes;
+
+    /// Test if the responses are read from the matrix and undeclared pairs are ignored
+    #[test]
+    fn test_collision_matrix() {
+        let matrix =
+            from_bytes::<CollisionMatrix>(include_bytes!("../../assets/resources/collision.ron"))
+                .unwrap();
+
+        assert_eq!(
+            matrix.get(Collider::Player, Collider::Solid),
+            CollisionResponse::Block
+        );
+        assert_eq!(
+            matrix.get(Collider::PlayerBullet, Collider::Enemy),
+            CollisionResponse::Damage
+        );
+        assert_eq!(
+            matrix.get(Collider::Player, Collider::EnemyBullet),
+            CollisionResponse::TakeDamage
+        );
+
+        // Flying enemies pass the inner walls, but not the outer walls of the room
+        assert_eq!(
+            matrix.get(Collider::FlyingEnemy, Collider::Solid),
+            CollisionResponse::Ignore
+        );
+        assert_eq!(
+            matrix.get(Collider::FlyingEnemy, Collider::Boundary),
+            CollisionResponse::Block
+        );
+    }
+
+    /// Test if the damaged and the damaging entity are taken from the direction of the response
+    #[test]
+    fn test_collision_damage() {
+        let (entity, other) = (Entity::from_raw(1), Entity::from_raw(2));
+        let event = |response| CollisionEvent {
+            entity,
+            other,
+            colliders: (Collider::Player, Collider::Enemy),
+            response,
+        };
+
+        assert_eq!(
+            event(CollisionResponse::Damage).damage(),
+            Some((other, entity))
+        );
+        assert_eq!(
+            event(CollisionResponse::TakeDamage).damage(),
+            Some((entity, other))
+        );
+        assert_eq!(event(CollisionResponse::Block).damage(), None);
+        assert_eq!(event(CollisionResponse::Trigger).damage(), None);
+    }
+}
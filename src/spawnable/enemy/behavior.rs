@@ -3,19 +3,37 @@ use rand::Rng;
 use serde::Deserialize;
 
 use crate::{
-    map::map_generation::RoomPos,
+    map::{
+        map_generation::RoomPos,
+        pathfinding::{find_path, NavigationGrid, NavigationGrids, TilePos},
+        TILE_SIZE,
+    },
     menu::AppState,
+    rng::GameRng,
     spawnable::{
         behavior::{Health, Spawnable},
-        enemy::enemy_types::Boss,
+        enemy::{boss::BossPhase, enemy_types::Boss},
         movement::Movement,
         player::Player,
+        status_effect::{StatusEffect, StatusEffects},
         weapon::{WeaponList, WeaponTypes},
     },
 };
 
 pub struct EnemyPlugin;
 
+// --- Constants ---
+/// Distance at which an enemy has reached a tile of its path
+const WAYPOINT_DISTANCE: f32 = TILE_SIZE / 4.0;
+/// Seconds until a wandering enemy changes its direction
+const WANDER_INTERVAL: f32 = 1.5;
+/// Factor of the enemy speed while wandering
+const WANDER_SPEED_FACTOR: f32 = 0.5;
+/// Seconds a charge lasts
+const CHARGE_DURATION: f32 = 0.4;
+/// Minimum distance to the player after a teleport
+const TELEPORT_MIN_DISTANCE: f32 = 4.0 * TILE_SIZE;
+
 // --- Execute systems ---
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
@@ -49,84 +67,522 @@ pub struct Enemy {
     pub damage: f32,
     pub weight: f32,
     pub behavior: Vec<EnemyBehavior>,
+    #[serde(default)]
+    pub states: Vec<EnemyState>,
     pub weapon: Vec<WeaponTypes>,
     pub is_boss: bool,
+    #[serde(default)]
+    pub phases: Vec<BossPhase>,
+    #[serde(default)]
+    pub flying: bool,
+    #[serde(default)]
+    pub contact_effects: Vec<StatusEffect>,
 }
 
 /// Enemy behavior for the enemy
+///
+/// * `MoveToPlayer` - Moves in a straight line to the player until it is in range
+/// * `PathToPlayer` - Follows a path around walls to the player until it is in range
+/// * `ShootAtPlayer` - Shoots at the player with a random weapon
+/// * `Wander` - Moves in a random direction which changes from time to time
+/// * `Patrol` - Moves between points, given in tiles relative to the spawn position
+/// * `Flee` - Moves away from the player while it is in range
+/// * `Charge` - Stands still for the windup, then dashes at the player and waits for the cooldown
+/// * `Orbit` - Circles around the player with the given radius
+/// * `Teleport` - Teleports to a random tile of the room after each cooldown
+/// * `KeepDistance` - Moves so that the distance to the player stays between min and max
 #[derive(Deserialize, Clone)]
 pub enum EnemyBehavior {
     MoveToPlayer(f32),
+    PathToPlayer(f32),
     ShootAtPlayer,
+    Wander,
+    Patrol(Vec<Vec2>),
+    Flee(f32),
+    Charge {
+        windup: f32,
+        speed: f32,
+        cooldown: f32,
+    },
+    Orbit(f32),
+    Teleport {
+        cooldown: f32,
+    },
+    KeepDistance(f32, f32),
+}
+
+/// State of an enemy with its own behaviors, which are used while the condition is met
+///
+/// The first state whose condition is met replaces the default behaviors of the enemy.
+#[derive(Deserialize, Clone)]
+pub struct EnemyState {
+    pub condition: BehaviorCondition,
+    pub behavior: Vec<EnemyBehavior>,
+}
+
+/// Condition to switch to another state
+///
+/// * `HealthBelow` - The health is below the given fraction of the maximum health
+/// * `PlayerCloserThan` - The player is closer than the given distance
+/// * `PlayerFurtherThan` - The player is further away than the given distance
+/// * `TimeAbove` - The enemy is alive for more than the given seconds
+/// * `Periodic` - Met for `duration` seconds at the end of every `period` seconds
+/// * `All` - All conditions are met
+#[derive(Deserialize, Clone)]
+pub enum BehaviorCondition {
+    HealthBelow(f32),
+    PlayerCloserThan(f32),
+    PlayerFurtherThan(f32),
+    TimeAbove(f32),
+    Periodic { period: f32, duration: f32 },
+    All(Vec<BehaviorCondition>),
+}
+
+/// Runtime information of the behaviors of an enemy
+///
+/// * `origin` - The spawn position of the enemy
+/// * `alive_time` - Seconds since the enemy was spawned
+/// * `active_state` - Index of the active state, `None` if the default behaviors are used
+/// * `wander_direction` - Current direction of `Wander`
+/// * `wander_time` - Seconds until `Wander` changes its direction
+/// * `patrol_index` - Index of the next point of `Patrol`
+/// * `charge` - Current phase of `Charge`
+/// * `teleport_cooldown` - Seconds until the next `Teleport`
+#[derive(Component, Clone, Default)]
+pub struct BehaviorState {
+    pub origin: Vec2,
+    pub alive_time: f32,
+    pub active_state: Option<usize>,
+    pub wander_direction: Vec2,
+    pub wander_time: f32,
+    pub patrol_index: usize,
+    pub charge: ChargePhase,
+    pub teleport_cooldown: f32,
+}
+
+/// Phases of the charge behavior
+///
+/// * `Ready` - Waiting for the cooldown (in seconds) to start the next windup
+/// * `Windup` - Standing still for the remaining seconds
+/// * `Charging` - Dashing in the direction for the remaining seconds
+#[derive(Clone, Copy)]
+pub enum ChargePhase {
+    Ready(f32),
+    Windup(f32),
+    Charging(Vec2, f32),
+}
+
+/// Path of an enemy to the player
+///
+/// * `path` - The remaining tiles of the path
+/// * `player_tile` - The tile of the player when the path was planned
+#[derive(Component, Clone)]
+pub struct EnemyPath {
+    pub path: Vec<TilePos>,
+    pub player_tile: TilePos,
 }
 
 pub struct EnemySlainEvent(pub RoomPos, pub Entity);
 
+// --- Methods for Structs ---
+impl BehaviorCondition {
+    /// Check if the condition is met
+    ///
+    /// # Arguments
+    /// * `health` - The health of the enemy
+    /// * `distance` - The distance between the enemy and the player
+    /// * `alive_time` - Seconds since the enemy was spawned
+    pub fn is_met(&self, health: &Health, distance: f32, alive_time: f32) -> bool {
+        match self {
+            BehaviorCondition::HealthBelow(fraction) => {
+                health.health < health.max_health * fraction
+            }
+            BehaviorCondition::PlayerCloserThan(range) => distance < *range,
+            BehaviorCondition::PlayerFurtherThan(range) => distance > *range,
+            BehaviorCondition::TimeAbove(time) => alive_time > *time,
+            BehaviorCondition::Periodic { period, duration } => {
+                *period > 0.0 && alive_time.rem_euclid(*period) > period - duration
+            }
+            BehaviorCondition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.is_met(health, distance, alive_time)),
+        }
+    }
+}
+
+impl BehaviorState {
+    /// Create the behavior state of a newly spawned enemy
+    pub fn new(origin: Vec2) -> BehaviorState {
+        BehaviorState {
+            origin,
+            ..Default::default()
+        }
+    }
+
+    /// Switch to another state and reset the behaviors which shouldn't continue in it
+    fn switch_state(&mut self, active_state: Option<usize>) {
+        self.active_state = active_state;
+        self.patrol_index = 0;
+        self.charge = ChargePhase::default();
+    }
+}
+
+impl Default for ChargePhase {
+    fn default() -> Self {
+        ChargePhase::Ready(0.0)
+    }
+}
+
 // --- System-Functions ---
 /// Execeutes enemy behavior
+///
+/// Chooses the behaviors of the active state of each enemy and executes them
 fn enemy_behaviour(
     mut commands: Commands,
     mut enemy_query: Query<
-        (&Enemy, &Transform, &Spawnable, Entity),
+        (
+            &Enemy,
+            &mut Transform,
+            &Spawnable,
+            &Health,
+            &RoomPos,
+            &mut BehaviorState,
+            Option<&mut EnemyPath>,
+            Option<&StatusEffects>,
+            Entity,
+        ),
         (
             With<Enemy>,
             Without<Player>,
             Or<(Without<Hitstun>, With<Boss>)>,
         ),
     >,
-    mut weapon_list_query: Query<(&mut WeaponList, Entity), With<Enemy>>,
-    mut player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
-    mut time: Res<Time>,
+    mut weapon_list_query: Query<&mut WeaponList, With<Enemy>>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    navigation_grids: Res<NavigationGrids>,
 ) {
-    for (enemy, enemy_transform, spawnable, entity) in enemy_query.iter_mut() {
-        for behavior in enemy.behavior.iter() {
+    let player_position = player_query.single().translation.truncate();
+    let delta = time.delta_seconds();
+
+    for (
+        enemy,
+        mut enemy_transform,
+        spawnable,
+        health,
+        room_pos,
+        mut state,
+        mut enemy_path,
+        status_effects,
+        entity,
+    ) in enemy_query.iter_mut()
+    {
+        let (speed_factor, fire_rate_factor) = status_effects.map_or((1.0, 1.0), |effects| {
+            (effects.speed_factor(), effects.fire_rate_factor())
+        });
+
+        let enemy_position = enemy_transform.translation.truncate();
+        let distance = enemy_position.distance(player_position);
+        let grid = navigation_grids.grids.get(room_pos);
+
+        state.alive_time += delta;
+
+        // Switch to the first state whose condition is met
+        let alive_time = state.alive_time;
+        let active_state = enemy
+            .states
+            .iter()
+            .position(|enemy_state| enemy_state.condition.is_met(health, distance, alive_time));
+        if active_state != state.active_state {
+            state.switch_state(active_state);
+        }
+
+        let behaviors = match active_state {
+            Some(index) => &enemy.states[index].behavior,
+            None => &enemy.behavior,
+        };
+
+        for behavior in behaviors.iter() {
+            // Direction and speed to move in this frame
+            let mut movement: Option<(Vec2, f32)> = None;
+
             match behavior {
                 EnemyBehavior::MoveToPlayer(range) => {
-                    let player_transform = player_query.single_mut();
-
-                    let direction = player_transform.translation - enemy_transform.translation;
+                    if distance > *range {
+                        movement = Some((player_position - enemy_position, enemy.speed));
+                    }
+                }
+                EnemyBehavior::PathToPlayer(range) => {
+                    if distance > *range {
+                        // Move directly to the player if no path can be found
+                        let direction = grid
+                            .and_then(|grid| {
+                                get_path_direction(
+                                    &mut commands,
+                                    entity,
+                                    enemy_path.as_deref_mut(),
+                                    grid,
+                                    enemy_position,
+                                    player_position,
+                                )
+                            })
+                            .unwrap_or(player_position - enemy_position);
 
-                    if direction.length() > *range && !spawnable.despawn {
-                        commands.entity(entity).insert(Movement {
-                            direction: Vec2::new(direction.x, direction.y).normalize(),
-                            transform: *enemy_transform,
-                            speed: enemy.speed,
-                        });
+                        movement = Some((direction, enemy.speed));
                     }
                 }
                 EnemyBehavior::ShootAtPlayer => {
-                    let player_transform = player_query.single_mut();
-
-                    let direction = Vec2::new(
-                        player_transform.translation.x - enemy_transform.translation.x,
-                        player_transform.translation.y - enemy_transform.translation.y,
-                    )
-                    .normalize();
-
-                    for (mut weapon_list, weapon_entity) in weapon_list_query.iter_mut() {
-                        if weapon_entity == entity {
-                            //Shoot random weapon
-                            let weapon = &mut weapon_list.weapons
-                                [rand::thread_rng().gen_range(0..enemy.weapon.len())];
-
-                            weapon.shoot_weapon(
-                                &mut commands,
-                                &mut time,
-                                direction,
-                                Vec2::new(
-                                    enemy_transform.translation.x,
-                                    enemy_transform.translation.y,
-                                ),
-                                false,
-                            )
+                    let direction = (player_position - enemy_position).normalize();
+
+                    if let Ok(mut weapon_list) = weapon_list_query.get_mut(entity) {
+                        //Shoot random weapon
+                        let weapon = &mut weapon_list.weapons[rng.gen_range(0..enemy.weapon.len())];
+
+                        weapon.shoot_weapon(
+                            &mut commands,
+                            delta * fire_rate_factor,
+                            direction,
+                            enemy_position,
+                            false,
+                            &mut rng,
+                        )
+                    }
+                }
+                EnemyBehavior::Wander => {
+                    state.wander_time -= delta;
+
+                    if state.wander_time <= 0.0 {
+                        state.wander_direction =
+                            Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                        state.wander_time = WANDER_INTERVAL;
+                    }
+
+                    movement = Some((state.wander_direction, enemy.speed * WANDER_SPEED_FACTOR));
+                }
+                EnemyBehavior::Patrol(points) => {
+                    if let Some(target) =
+                        get_patrol_target(&mut state, points, grid, enemy_position)
+                    {
+                        movement = Some((target - enemy_position, enemy.speed));
+                    }
+                }
+                EnemyBehavior::Flee(range) => {
+                    if distance < *range {
+                        movement = Some((enemy_position - player_position, enemy.speed));
+                    }
+                }
+                EnemyBehavior::Charge {
+                    windup,
+                    speed,
+                    cooldown,
+                } => {
+                    state.charge = match state.charge {
+                        ChargePhase::Ready(remaining) if remaining - delta <= 0.0 => {
+                            ChargePhase::Windup(*windup)
+                        }
+                        ChargePhase::Ready(remaining) => {
+                            movement = Some((player_position - enemy_position, enemy.speed));
+                            ChargePhase::Ready(remaining - delta)
+                        }
+                        ChargePhase::Windup(remaining) if remaining - delta <= 0.0 => {
+                            ChargePhase::Charging(player_position - enemy_position, CHARGE_DURATION)
+                        }
+                        ChargePhase::Windup(remaining) => ChargePhase::Windup(remaining - delta),
+                        ChargePhase::Charging(_, remaining) if remaining - delta <= 0.0 => {
+                            ChargePhase::Ready(*cooldown)
+                        }
+                        ChargePhase::Charging(direction, remaining) => {
+                            movement = Some((direction, *speed));
+                            ChargePhase::Charging(direction, remaining - delta)
+                        }
+                    };
+                }
+                EnemyBehavior::Orbit(radius) => {
+                    let outwards = enemy_position - player_position;
+
+                    if outwards != Vec2::ZERO {
+                        // Move along the circle and correct the distance to the player
+                        let tangent = outwards.normalize().perp();
+                        let correction =
+                            outwards.normalize() * (radius - distance) / radius.max(1.0);
+
+                        movement = Some((tangent + correction, enemy.speed));
+                    }
+                }
+                EnemyBehavior::Teleport { cooldown } => {
+                    state.teleport_cooldown -= delta;
+
+                    if state.teleport_cooldown <= 0.0 {
+                        state.teleport_cooldown = *cooldown;
+
+                        if let Some(position) = grid
+                            .and_then(|grid| get_teleport_position(grid, player_position, &mut rng))
+                        {
+                            enemy_transform.translation.x = position.x;
+                            enemy_transform.translation.y = position.y;
                         }
                     }
                 }
+                EnemyBehavior::KeepDistance(min, max) => {
+                    if distance < *min {
+                        movement = Some((enemy_position - player_position, enemy.speed));
+                    } else if distance > *max {
+                        movement = Some((player_position - enemy_position, enemy.speed));
+                    }
+                }
+            }
+
+            if let Some((direction, speed)) = movement {
+                if direction != Vec2::ZERO && !spawnable.despawn {
+                    commands.entity(entity).insert(Movement {
+                        direction: direction.normalize(),
+                        transform: *enemy_transform,
+                        speed: speed * speed_factor,
+                    });
+                }
             }
         }
     }
 }
 
+/// Get the next point of a patrol in world coordinates
+///
+/// Points which are reached are skipped, as well as points which lie inside of walls.
+///
+/// # Arguments
+/// * `state` - The behavior state of the enemy
+/// * `points` - The points of the patrol in tiles relative to the spawn position
+/// * `grid` - The navigation grid of the room of the enemy
+/// * `enemy_position` - The position of the enemy
+///
+/// # Returns
+/// The position of the next point, or `None` if the patrol has no reachable points
+fn get_patrol_target(
+    state: &mut BehaviorState,
+    points: &[Vec2],
+    grid: Option<&NavigationGrid>,
+    enemy_position: Vec2,
+) -> Option<Vec2> {
+    for _ in 0..points.len() {
+        let index = state.patrol_index % points.len();
+        let target = state.origin + points[index] * TILE_SIZE;
+        let is_walkable = match grid {
+            Some(grid) => grid.is_walkable(grid.world_to_tile(target)),
+            None => true,
+        };
+
+        if is_walkable && target.distance(enemy_position) >= WAYPOINT_DISTANCE {
+            return Some(target);
+        }
+
+        state.patrol_index = (index + 1) % points.len();
+    }
+
+    None
+}
+
+/// Get a random position in the room which isn't too close to the player
+///
+/// # Arguments
+/// * `grid` - The navigation grid of the room
+/// * `player_position` - The position of the player
+/// * `rng` - The random number generator of the run
+///
+/// # Returns
+/// The center of a random walkable tile, or `None` if no tile is far enough away from the player
+fn get_teleport_position(
+    grid: &NavigationGrid,
+    player_position: Vec2,
+    rng: &mut GameRng,
+) -> Option<Vec2> {
+    let positions: Vec<Vec2> = grid
+        .walkable_tiles()
+        .into_iter()
+        .map(|tile| grid.tile_to_world(tile))
+        .filter(|position| position.distance(player_position) >= TELEPORT_MIN_DISTANCE)
+        .collect();
+
+    if positions.is_empty() {
+        None
+    } else {
+        Some(positions[rng.gen_range(0..positions.len())])
+    }
+}
+
+/// Get the direction to the next tile on the path to the player
+///
+/// The path is planned again when the player moves onto another tile or the enemy was pushed off the path.
+///
+/// # Arguments
+/// * `commands` - Commands to add the path to enemies without a path
+/// * `entity` - The entity of the enemy
+/// * `enemy_path` - The current path of the enemy
+/// * `grid` - The navigation grid of the room of the enemy
+/// * `enemy_position` - The position of the enemy
+/// * `player_position` - The position of the player
+///
+/// # Returns
+/// The direction to move in, or `None` if there is no path to the player
+fn get_path_direction(
+    commands: &mut Commands,
+    entity: Entity,
+    enemy_path: Option<&mut EnemyPath>,
+    grid: &NavigationGrid,
+    enemy_position: Vec2,
+    player_position: Vec2,
+) -> Option<Vec2> {
+    let enemy_tile = grid.world_to_tile(enemy_position);
+    let player_tile = grid.world_to_tile(player_position);
+
+    let mut path = match &enemy_path {
+        Some(enemy_path)
+            if enemy_path.player_tile == player_tile
+                && is_on_path(&enemy_path.path, enemy_tile) =>
+        {
+            enemy_path.path.clone()
+        }
+        _ => find_path(grid, enemy_tile, player_tile)?,
+    };
+
+    // Skip the tiles that have already been reached
+    while let Some(tile) = path.first() {
+        if grid.tile_to_world(*tile).distance(enemy_position) < WAYPOINT_DISTANCE {
+            path.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    // The player is on the same tile when the path is finished
+    let direction = match path.first() {
+        Some(tile) => grid.tile_to_world(*tile) - enemy_position,
+        None => player_position - enemy_position,
+    };
+
+    match enemy_path {
+        Some(enemy_path) => {
+            enemy_path.path = path;
+            enemy_path.player_tile = player_tile;
+        }
+        None => {
+            commands
+                .entity(entity)
+                .insert(EnemyPath { path, player_tile });
+        }
+    }
+
+    Some(direction)
+}
+
+/// Check if the next tile of a path is next to the tile of the enemy
+fn is_on_path(path: &[TilePos], enemy_tile: TilePos) -> bool {
+    match path.first() {
+        Some(tile) => (tile.x - enemy_tile.x).abs() <= 1 && (tile.y - enemy_tile.y).abs() <= 1,
+        None => true,
+    }
+}
+
 /// Checks if enemy is hitstunned and if so, moves enemy
 fn check_hitstun(
     mut hitstun_query: Query<
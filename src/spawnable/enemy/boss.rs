@@ -0,0 +1,342 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    menu::AppState,
+    rng::GameRng,
+    spawnable::{
+        behavior::{Health, Spawnable},
+        player::Player,
+        status_effect::{same_rgb, StatusEffects},
+        weapon::{WeaponList, WeaponResource, WeaponTypes},
+    },
+};
+
+use super::{
+    behavior::{Enemy, EnemyBehavior},
+    enemy_types::Boss,
+};
+
+pub struct BossPlugin;
+
+// --- Constants ---
+/// Color of the boss while an attack is telegraphed
+const TELEGRAPH_COLOR: Color = Color::rgb(1.0, 0.8, 0.0);
+
+// --- Execute systems ---
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame).with_system(boss_fight.before("step2")),
+        );
+    }
+}
+
+// --- Components and Structs ---
+/// Phase of a boss fight
+///
+/// * `health_threshold` - Fraction of the maximum health at which the phase starts
+/// * `movement` - Movement behaviors of the boss during the phase
+/// * `weapons` - Weapons of the boss during the phase
+/// * `attacks` - Attack sequence which is repeated during the phase
+#[derive(Deserialize, Clone)]
+pub struct BossPhase {
+    pub health_threshold: f32,
+    pub movement: Vec<EnemyBehavior>,
+    pub weapons: Vec<WeaponTypes>,
+    pub attacks: Vec<BossAttack>,
+}
+
+/// Attack of a boss phase
+///
+/// * `weapon` - Index of the weapon of the phase which is fired
+/// * `windup` - Seconds the attack is telegraphed before the weapon is fired
+/// * `duration` - Seconds the weapon is fired
+#[derive(Deserialize, Clone)]
+pub struct BossAttack {
+    pub weapon: usize,
+    pub windup: f32,
+    pub duration: f32,
+}
+
+/// Progress of the boss through its phases and attacks
+///
+/// * `phase` - Index of the current phase, `None` before the first phase started
+/// * `attack` - Index of the current attack of the phase
+/// * `attack_phase` - Current step of the attack
+#[derive(Component, Default)]
+pub struct BossFight {
+    pub phase: Option<usize>,
+    pub attack: usize,
+    pub attack_phase: AttackPhase,
+}
+
+/// Steps of a boss attack
+///
+/// * `Windup` - The attack is telegraphed for the remaining seconds
+/// * `Firing` - The weapon is fired for the remaining seconds
+#[derive(Clone, Copy)]
+pub enum AttackPhase {
+    Windup(f32),
+    Firing(f32),
+}
+
+impl Default for AttackPhase {
+    fn default() -> Self {
+        AttackPhase::Windup(0.0)
+    }
+}
+
+// --- Methods for Structs ---
+impl BossFight {
+    /// Start a phase of the boss fight
+    ///
+    /// The movement and the weapons of the boss are replaced by those of the phase
+    /// and the first attack of the phase is telegraphed.
+    ///
+    /// # Arguments
+    /// * `enemy` - The boss
+    /// * `index` - Index of the phase
+    /// * `weapon_res` - The weapons of the phase are taken from this resource
+    ///
+    /// # Returns
+    /// The weapons of the phase
+    pub fn enter_phase(
+        &mut self,
+        enemy: &mut Enemy,
+        index: usize,
+        weapon_res: &WeaponResource,
+    ) -> WeaponList {
+        let phase = enemy.phases[index].clone();
+
+        enemy.behavior = phase.movement;
+        enemy.weapon = phase.weapons.clone();
+
+        self.phase = Some(index);
+        self.attack = 0;
+        self.attack_phase =
+            AttackPhase::Windup(phase.attacks.first().map_or(0.0, |attack| attack.windup));
+
+        WeaponList {
+            weapons: phase
+                .weapons
+                .iter()
+                .map(|weapon| weapon_res.weapons[weapon].clone())
+                .collect(),
+            active: 0,
+        }
+    }
+}
+
+// --- Functions ---
+/// Get the phase of a boss for the current health
+///
+/// Phases are ordered by descending health thresholds, the last phase whose threshold is reached is used.
+///
+/// # Arguments
+/// * `phases` - The phases of the boss
+/// * `health` - The health of the boss
+pub fn get_boss_phase(phases: &[BossPhase], health: &Health) -> Option<usize> {
+    let fraction = health.health / health.max_health;
+
+    phases
+        .iter()
+        .rposition(|phase| fraction <= phase.health_threshold)
+}
+
+/// Get the phase the boss advances to
+///
+/// Phases only advance, healing doesn't return the boss to an earlier phase.
+///
+/// # Arguments
+/// * `current` - The current phase of the boss
+/// * `phases` - The phases of the boss
+/// * `health` - The health of the boss
+///
+/// # Returns
+/// The new phase, or None if the boss stays in its current phase
+pub fn next_boss_phase(
+    current: Option<usize>,
+    phases: &[BossPhase],
+    health: &Health,
+) -> Option<usize> {
+    get_boss_phase(phases, health).filter(|index| Some(*index) > current)
+}
+
+// --- Type aliases ---
+/// Query of the bosses with everything their fight changes
+type BossQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Enemy,
+        &'static mut BossFight,
+        &'static mut Sprite,
+        Option<&'static mut WeaponList>,
+        &'static Health,
+        &'static Transform,
+        &'static Spawnable,
+        Option<&'static StatusEffects>,
+        Entity,
+    ),
+    (With<Boss>, Without<Player>),
+>;
+
+// --- System-Functions ---
+/// Advances the boss through its phases and executes the attack sequence of the current phase
+///
+/// When a new phase starts, the movement and the weapons of the boss are replaced by those of the phase.
+/// Each attack is telegraphed by coloring the boss before its weapon is fired.
+/// The color isn't changed while another system shows its color, like the flash of the hitstun.
+fn boss_fight(
+    mut commands: Commands,
+    weapon_res: Res<WeaponResource>,
+    mut boss_query: BossQuery,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    let player_position = player_query.single().translation.truncate();
+
+    for (
+        mut enemy,
+        mut fight,
+        mut sprite,
+        weapon_list,
+        health,
+        transform,
+        spawnable,
+        status_effects,
+        entity,
+    ) in boss_query.iter_mut()
+    {
+        if spawnable.despawn {
+            continue;
+        }
+
+        if let Some(index) = next_boss_phase(fight.phase, &enemy.phases, health) {
+            let weapon_list = fight.enter_phase(&mut enemy, index, &weapon_res);
+            commands.entity(entity).insert(weapon_list);
+        }
+
+        let phase = match fight.phase {
+            Some(index) => &enemy.phases[index],
+            None => continue,
+        };
+        if phase.attacks.is_empty() {
+            continue;
+        }
+
+        let attack = &phase.attacks[fight.attack % phase.attacks.len()];
+        let delta = time.delta_seconds();
+        let mut color = None;
+
+        fight.attack_phase = match fight.attack_phase {
+            AttackPhase::Windup(remaining) if remaining - delta <= 0.0 => {
+                color = Some(enemy.color);
+                AttackPhase::Firing(attack.duration)
+            }
+            AttackPhase::Windup(remaining) => {
+                color = Some(TELEGRAPH_COLOR);
+                AttackPhase::Windup(remaining - delta)
+            }
+            AttackPhase::Firing(remaining) if remaining - delta <= 0.0 => {
+                // Telegraph the next attack of the sequence
+                fight.attack = (fight.attack + 1) % phase.attacks.len();
+                AttackPhase::Windup(phase.attacks[fight.attack].windup)
+            }
+            AttackPhase::Firing(remaining) => {
+                let position = transform.translation.truncate();
+
+                if let Some(weapon) = weapon_list
+                    .and_then(|weapon_list| weapon_list.into_inner().weapons.get_mut(attack.weapon))
+                {
+                    let fire_rate_factor =
+                        status_effects.map_or(1.0, |effects| effects.fire_rate_factor());
+
+                    weapon.shoot_weapon(
+                        &mut commands,
+                        delta * fire_rate_factor,
+                        (player_position - position).normalize(),
+                        position,
+                        false,
+                        &mut rng,
+                    );
+                }
+
+                AttackPhase::Firing(remaining - delta)
+            }
+        };
+
+        // Don't override a color of another system, the tint of the status effects is shown after the attack
+        let shows_own_color = same_rgb(sprite.color, enemy.color)
+            || same_rgb(sprite.color, TELEGRAPH_COLOR)
+            || status_effects.is_some_and(|effects| effects.shows_tint(sprite.color));
+        if let (Some(color), true) = (color, shows_own_color) {
+            let alpha = sprite.color.a();
+            sprite.color = color;
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Create boss phases with the given health thresholds
+    #[allow(dead_code)]
+    fn boss_phases(thresholds: &[f32]) -> Vec<BossPhase> {
+        thresholds
+            .iter()
+            .map(|health_threshold| BossPhase {
+                health_threshold: *health_threshold,
+                movement: vec![],
+                weapons: vec![],
+                attacks: vec![],
+            })
+            .collect()
+    }
+
+    /// Create the health of a boss with the given fraction of its maximum health
+    #[allow(dead_code)]
+    fn boss_health(fraction: f32) -> Health {
+        Health {
+            health: fraction * 100.0,
+            max_health: 100.0,
+        }
+    }
+
+    /// Test if the last phase whose threshold is reached is used
+    #[test]
+    fn test_get_boss_phase() {
+        let phases = boss_phases(&[1.0, 0.6, 0.3]);
+
+        assert_eq!(get_boss_phase(&phases, &boss_health(1.0)), Some(0));
+        assert_eq!(get_boss_phase(&phases, &boss_health(0.61)), Some(0));
+        assert_eq!(get_boss_phase(&phases, &boss_health(0.6)), Some(1));
+        assert_eq!(get_boss_phase(&phases, &boss_health(0.2)), Some(2));
+        assert_eq!(get_boss_phase(&phases, &boss_health(0.0)), Some(2));
+
+        // A boss above its first threshold has no phase yet
+        assert_eq!(
+            get_boss_phase(&boss_phases(&[0.5]), &boss_health(0.8)),
+            None
+        );
+    }
+
+    /// Test if the phases only advance and healing doesn't return the boss to an earlier phase
+    #[test]
+    fn test_next_boss_phase() {
+        let phases = boss_phases(&[1.0, 0.6, 0.3]);
+
+        assert_eq!(next_boss_phase(None, &phases, &boss_health(1.0)), Some(0));
+        assert_eq!(next_boss_phase(Some(0), &phases, &boss_health(0.9)), None);
+        assert_eq!(
+            next_boss_phase(Some(0), &phases, &boss_health(0.2)),
+            Some(2)
+        );
+        assert_eq!(next_boss_phase(Some(2), &phases, &boss_health(1.0)), None);
+        assert_eq!(next_boss_phase(Some(2), &phases, &boss_health(0.5)), None);
+    }
+}
@@ -1,40 +1,47 @@
 use std::collections::HashMap;
 
-use bevy::prelude::*;
-use rand::{distributions::Standard, prelude::Distribution, Rng};
-use serde::Deserialize;
+use bevy::{prelude::*, reflect::TypeUuid};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     map::map_generation::RoomPos,
+    rng::GameRng,
+    save::EnemyState,
     spawnable::{
         behavior::{Health, Spawnable},
         movement::Collider,
+        status_effect::StatusEffects,
         weapon::{Weapon, WeaponList, WeaponResource, WeaponTypes},
     },
 };
 
-use super::behavior::Enemy;
+use super::{
+    behavior::{BehaviorState, Enemy},
+    boss::BossFight,
+};
 
 /// Boss-Component as a marker for the boss
 #[derive(Component)]
 pub struct Boss {}
 
 /// Enemy-Resource for loading enemys from ron file
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, TypeUuid)]
+#[uuid = "fbe83542-bbaf-472a-9cf7-8febc7ee83b8"]
 pub struct EnemyResource {
-    enemys: HashMap<EnemyType, EnemyRon>,
+    pub enemys: HashMap<EnemyType, EnemyRon>,
 }
 
 /// Struct for the enemy-resource, containing the enemy itself, the health and the behavior
 #[derive(Deserialize, Clone)]
 pub struct EnemyRon {
-    data: Enemy,
-    health: Health,
-    behavior: Spawnable,
+    pub data: Enemy,
+    pub health: Health,
+    pub behavior: Spawnable,
 }
 
 /// Enemy-Type as a marker for the enemy
-#[derive(Component, Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum EnemyType {
     BigBlob,
     MediumBlob,
@@ -49,24 +56,41 @@ pub enum EnemyType {
     Boss,
 }
 
-impl Distribution<EnemyType> for Standard {
-    /// Get a random enemytype (excluding the boss)
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> EnemyType {
-        match rng.gen_range(0..=7) {
-            0 => EnemyType::BigBlob,
-            1 => EnemyType::PistolEnemy,
-            2 => EnemyType::SplitShotEnemy,
-            3 => EnemyType::ShotgunEnemy,
-            4 => EnemyType::SniperEnemy,
-            5 => EnemyType::CrossEnemy,
-            6 => EnemyType::CircleEnemy,
-            _ => EnemyType::MachineGunEnemy,
+impl EnemyType {
+    /// Get a random enemytype of the enemy pool of a floor (excluding the boss)
+    pub fn random_for_floor(floor: i32, rng: &mut GameRng) -> EnemyType {
+        let pool = EnemyType::pool_for_floor(floor);
+        pool[rng.gen_range(0..pool.len())].clone()
+    }
+
+    /// Get the enemy pool of a floor (excluding the boss)
+    ///
+    /// Deeper floors add stronger enemies to the pool
+    pub fn pool_for_floor(floor: i32) -> Vec<EnemyType> {
+        let mut pool = vec![
+            EnemyType::BigBlob,
+            EnemyType::PistolEnemy,
+            EnemyType::SplitShotEnemy,
+            EnemyType::ShotgunEnemy,
+        ];
+
+        if floor >= 2 {
+            pool.push(EnemyType::SniperEnemy);
+            pool.push(EnemyType::MachineGunEnemy);
+        }
+        if floor >= 3 {
+            pool.push(EnemyType::CrossEnemy);
+            pool.push(EnemyType::CircleEnemy);
         }
+
+        pool
     }
 }
 
 // --- Functions ---
 /// Spawns an enemy with a given type by using the enemy-resource
+///
+/// An enemy with a saved state continues with its saved health and boss phase.
 pub fn spawn_enemy_type(
     commands: &mut Commands,
     enemy_res: &EnemyResource,
@@ -74,6 +98,7 @@ pub fn spawn_enemy_type(
     enemy_type: &EnemyType,
     position: Vec2,
     room_pos: RoomPos,
+    state: Option<&EnemyState>,
 ) {
     let enemy = &enemy_res.enemys[enemy_type];
     let mut entity = commands.spawn_bundle(SpriteBundle {
@@ -91,11 +116,17 @@ pub fn spawn_enemy_type(
 
     entity
         .insert(enemy.data.clone())
-        .insert(enemy.health.clone())
+        .insert(state.map_or(enemy.health.clone(), |state| state.health.clone()))
         .insert(enemy.behavior.clone())
+        .insert(BehaviorState::new(position))
         .insert(enemy_type.clone())
-        .insert(Collider::Enemy)
-        .insert(room_pos);
+        .insert(if enemy.data.flying {
+            Collider::FlyingEnemy
+        } else {
+            Collider::Enemy
+        })
+        .insert(room_pos)
+        .insert(StatusEffects::default());
 
     let mut weapon_list: Vec<Weapon> = Vec::new();
     for weapon in &enemy.data.weapon {
@@ -108,10 +139,23 @@ pub fn spawn_enemy_type(
     if !weapon_list.is_empty() {
         entity.insert(WeaponList {
             weapons: weapon_list,
+            active: 0,
         });
     }
 
     if enemy.data.is_boss {
-        entity.insert(Boss {});
+        let mut fight = BossFight::default();
+
+        // The saved phase starts again, its attack sequence is repeated anyway
+        if let Some(index) = state
+            .and_then(|state| state.boss_phase)
+            .filter(|index| *index < enemy.data.phases.len())
+        {
+            let mut data = enemy.data.clone();
+            let weapon_list = fight.enter_phase(&mut data, index, weapon_res);
+            entity.insert(data).insert(weapon_list);
+        }
+
+        entity.insert(Boss {}).insert(fight);
     }
 }
@@ -1,2 +1,3 @@
 pub mod behavior;
+pub mod boss;
 pub mod enemy_types;
\ No newline at end of file
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use crate::{
+    map::{map_generation::RoomPos, shop::ShopPrice},
+    menu::AppState,
+    spawnable::{
+        behavior::Health,
+        movement::check_collision,
+        player::Player,
+        weapon::{Weapon, WeaponList},
+    },
+};
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+pub struct ItemPlugin;
+
+// --- Constants ---
+/// Chance that a finished normal room drops an item
+pub const ITEM_DROP_CHANCE: f32 = 0.2;
+/// Every item type, each of them must be defined in the ron file
+pub const ITEM_TYPES: [ItemType; 7] = [
+    ItemType::SpeedBoots,
+    ItemType::HeartContainer,
+    ItemType::PowerShot,
+    ItemType::RapidFire,
+    ItemType::Accelerator,
+    ItemType::Focus,
+    ItemType::TripleShot,
+];
+
+// --- Execute systems ---
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame).with_system(pickup_item.after("step3")),
+        );
+    }
+}
+
+// --- Components and Structs ---
+/// Item-Resource for loading items from ron file
+#[derive(Deserialize, Clone)]
+pub struct ItemResource {
+    pub items: HashMap<ItemType, Item>,
+}
+
+/// Item with the modifiers that are applied on pickup
+#[derive(Deserialize, Clone)]
+pub struct Item {
+    pub name: String,
+    pub color: Color,
+    pub modifiers: Vec<StatModifier>,
+}
+
+/// Stat modifiers of an item
+///
+/// * `Speed` - Adds to the movement speed of the player
+/// * `MaxHealth` - Adds to the maximum health of the player and heals by the same amount
+/// * `Damage` - Multiplies the damage of the weapons
+/// * `FireRate` - Multiplies the time between two shots of the weapons
+/// * `BulletSpeed` - Multiplies the bullet speed of the weapons
+/// * `Spread` - Multiplies the spread of the weapons
+/// * `ShootingPattern` - Replaces the shooting pattern of the weapons
+#[derive(Deserialize, Clone)]
+pub enum StatModifier {
+    Speed(f32),
+    MaxHealth(f32),
+    Damage(f32),
+    FireRate(f32),
+    BulletSpeed(f32),
+    Spread(f32),
+    ShootingPattern(Vec<f32>),
+}
+
+/// Enum for item types (values are defined in the ron file)
+#[derive(Component, Serialize, Deserialize, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum ItemType {
+    SpeedBoots,
+    HeartContainer,
+    PowerShot,
+    RapidFire,
+    Accelerator,
+    Focus,
+    TripleShot,
+}
+
+/// List of the items the player collected
+#[derive(Component, Clone)]
+pub struct ItemList {
+    pub items: Vec<ItemType>,
+}
+
+/// Item pickup which can be collected by the player
+#[derive(Component)]
+pub struct ItemPickup {
+    pub item_type: ItemType,
+}
+
+// --- Methods for Structs ---
+impl ItemResource {
+    /// Returns a random item of the ron file
+    ///
+    /// The item types are sorted, so the same seed chooses the same item
+    ///
+    /// # Arguments
+    /// * `rng` - The random number generator of the run
+    ///
+    /// # Returns
+    /// The item type, or None if no item is defined
+    pub fn random_item<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<ItemType> {
+        let mut item_types: Vec<&ItemType> = self.items.keys().collect();
+        item_types.sort();
+
+        item_types.choose(rng).map(|item_type| (*item_type).clone())
+    }
+}
+
+impl Item {
+    /// Applies the modifiers of the item to the player and the weapons
+    pub fn apply(&self, player: &mut Player, health: &mut Health, weapon_list: &mut WeaponList) {
+        for modifier in self.modifiers.iter() {
+            match modifier {
+                StatModifier::Speed(speed) => {
+                    player.speed += speed;
+                }
+                StatModifier::MaxHealth(max_health) => {
+                    health.max_health += max_health;
+                    health.health += max_health;
+                }
+                _ => {}
+            }
+        }
+
+        for weapon in weapon_list.weapons.iter_mut() {
+            self.apply_to_weapon(weapon);
+        }
+    }
+
+    /// Applies the weapon modifiers of the item to a weapon
+    pub fn apply_to_weapon(&self, weapon: &mut Weapon) {
+        for modifier in self.modifiers.iter() {
+            match modifier {
+                StatModifier::Damage(damage) => {
+                    weapon.damage *= damage;
+                }
+                StatModifier::FireRate(fire_rate) => {
+                    weapon.fire_rate.max *= fire_rate;
+                }
+                StatModifier::BulletSpeed(speed) => {
+                    weapon.speed *= speed;
+                }
+                StatModifier::Spread(spread) => {
+                    weapon.spread *= spread;
+                }
+                StatModifier::ShootingPattern(shooting_pattern) => {
+                    weapon.shooting_pattern = shooting_pattern.clone();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// --- Functions ---
+/// Spawns an item pickup at the given position, if the item is defined
+///
+/// # Returns
+/// The entity of the pickup, or None if the item isn't defined
+pub fn spawn_item(
+    commands: &mut Commands,
+    item_res: &ItemResource,
+    item_type: ItemType,
+    position: Vec2,
+    room_pos: RoomPos,
+) -> Option<Entity> {
+    let item = item_res.items.get(&item_type)?;
+
+    let entity = commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: position.extend(1.0),
+                scale: Vec3::new(20.0, 20.0, 0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: item.color,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ItemPickup { item_type })
+        .insert(Name::new(item.name.clone()))
+        .insert(room_pos)
+        .id();
+
+    Some(entity)
+}
+
+// --- System-Functions ---
+/// Collects the items the player touches and applies their modifiers
+///
+/// Items in a shop are only collected if the player can pay their price.
+fn pickup_item(
+    mut commands: Commands,
+    item_res: Res<ItemResource>,
+    item_query: Query<(&ItemPickup, &Transform, Option<&ShopPrice>, Entity), Without<Player>>,
+    mut player_query: Query<(
+        &mut Player,
+        &mut Health,
+        &mut WeaponList,
+        &mut ItemList,
+        &Transform,
+    )>,
+) {
+    let (mut player, mut health, mut weapon_list, mut item_list, player_transform) =
+        player_query.single_mut();
+
+    for (item_pickup, item_transform, price, entity) in item_query.iter() {
+        if check_collision(player_transform, item_transform) && ShopPrice::pay(price, &mut player) {
+            if let Some(item) = item_res.items.get(&item_pickup.item_type) {
+                item.apply(&mut player, &mut health, &mut weapon_list);
+                item_list.items.push(item_pickup.item_type.clone());
+            }
+
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+    use crate::rng::GameRng;
+    use ron::de::from_bytes;
+
+    /// Test if random items are chosen from the ron file and the same seed chooses the same items
+    #[test]
+    fn test_random_item() {
+        let mut item_res =
+            from_bytes::<ItemResource>(include_bytes!("../../assets/resources/item.ron")).unwrap();
+
+        let choose = |item_res: &ItemResource| {
+            let mut rng = GameRng::new(Some(7));
+            (0..20)
+                .map(|_| item_res.random_item(&mut rng).unwrap())
+                .collect::<Vec<ItemType>>()
+        };
+        assert_eq!(choose(&item_res), choose(&item_res.clone()));
+
+        item_res
+            .items
+            .retain(|item_type, _| *item_type == ItemType::Focus);
+        assert!(choose(&item_res)
+            .iter()
+            .all(|item_type| *item_type == ItemType::Focus));
+
+        item_res.items.clear();
+        assert_eq!(item_res.random_item(&mut GameRng::new(Some(7))), None);
+    }
+}
@@ -1,6 +1,10 @@
 pub mod behavior;
 pub mod bullet;
+pub mod collision;
 pub mod enemy;
+pub mod item;
 pub mod movement;
 pub mod player;
+pub mod spatial_hash;
+pub mod status_effect;
 pub mod weapon;
@@ -1,14 +1,18 @@
 use bevy::{prelude::*, sprite::collide_aabb::collide};
 
 use crate::{
-    map::{
-        map_generation::RoomPos,
-        room::{LeaveRoomEvent, TransitionDirection},
-    },
+    map::{calc_room_pos, is_in_room, TILE_SIZE},
     menu::AppState,
-    spawnable::behavior::{Spawnable, TakeDamageEvent},
+    spawnable::{
+        behavior::{Spawnable, TakeDamageEvent},
+        bullet::{Bullet, BulletImpact},
+        collision::{CollisionEvent, CollisionMatrix, CollisionResponse},
+        player::Player,
+        spatial_hash::SpatialHash,
+    },
     TIME_STEP,
 };
+use serde::Deserialize;
 
 // --- Constants ---
 pub const DEFAULT_HITSTUN_DURATION: f32 = 8.0;
@@ -22,6 +26,7 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<TakeDamageEvent>()
             .add_event::<MoveEntity>()
+            .add_event::<CollisionEvent>()
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
                     .label("step2")
@@ -50,19 +55,45 @@ pub struct Movement {
 pub struct MoveEntity {}
 
 /// Collider component for entities, defines collider-type
-#[derive(Component, Clone, Copy)]
+///
+/// The collider is the collision layer of an entity, the collision matrix defines how the layers interact.
+/// The outer walls of a room are `Boundary` instead of `Solid`, so flying enemies can pass the inner walls,
+/// but can't leave the room.
+#[derive(Component, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Collider {
     Solid,
+    Boundary,
+    Door,
     Enemy,
+    FlyingEnemy,
     Player,
+    RollingPlayer,
     EnemyBullet,
     PlayerBullet,
     RoomTransition,
+    Trapdoor,
+    SecretWall,
+    Pit,
+    Rock,
+}
+
+impl Collider {
+    /// Check if the collider belongs to a bullet
+    pub fn is_bullet(&self) -> bool {
+        matches!(self, Collider::EnemyBullet | Collider::PlayerBullet)
+    }
 }
 
 // --- System-Functions ---
 /// Moves entities theoretically and checks for collisions to resolve them
-fn movement_and_collision(
+///
+/// The response to a collision is taken from the collision matrix and sent as a collision event.
+/// Only colliders in the room of the player are checked. They are sorted into a spatial hash with cells
+/// of the size of a tile, so each step only checks the colliders next to the moving entity.
+///
+/// Bullets use up their pierces and bounces on collisions. When a bullet stops or its lifetime is over,
+/// an explosive bullet sends a damage collision to every collider in its radius which it could damage.
+pub fn movement_and_collision(
     time: Res<Time>,
     mut movement_query: Query<
         (
@@ -70,19 +101,20 @@ fn movement_and_collision(
             &Transform,
             &Collider,
             Option<&mut Spawnable>,
+            Option<&mut Bullet>,
             Entity,
         ),
         With<Movement>,
     >,
-    mut take_damage: EventWriter<TakeDamageEvent>,
     mut move_entity: EventWriter<MoveEntity>,
+    mut ev_collision: EventWriter<CollisionEvent>,
+    collision_matrix: Res<CollisionMatrix>,
     collider_query: Query<(&Collider, &Transform, Entity)>,
-    transition_query: Query<(&RoomPos, &TransitionDirection)>,
-    mut ev_leave_room: EventWriter<LeaveRoomEvent>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
     let mut max_movement = 0.0;
 
-    for (mut movement, transform, _collider, _spawnable_option, _entity) in
+    for (mut movement, transform, _collider, _spawnable_option, _bullet_option, _entity) in
         movement_query.iter_mut()
     {
         // normalize direction vector
@@ -98,8 +130,56 @@ fn movement_and_collision(
     //Get the time step for adjusting speed based on time
     let time_step = time.delta_seconds() / TIME_STEP as f32;
 
+    // Sort the colliders of the active room into the spatial hash
+    let active_room = player_query
+        .get_single()
+        .ok()
+        .map(|transform| calc_room_pos(transform.translation.truncate()));
+
+    let colliders: Vec<(&Collider, &Transform, Entity)> = collider_query
+        .iter()
+        .filter(|(_, transform, _)| match active_room {
+            Some(room_pos) => is_in_room(room_pos, transform.translation.truncate()),
+            None => true,
+        })
+        .collect();
+
+    let mut spatial_hash = SpatialHash::new(TILE_SIZE);
+    for (index, (_, transform, _)) in colliders.iter().enumerate() {
+        spatial_hash.insert(
+            index,
+            transform.translation.truncate(),
+            transform.scale.truncate(),
+        );
+    }
+
+    let mut candidates: Vec<usize> = vec![];
+    // Exploding bullets with their position and the entity they hit directly
+    let mut explosions: Vec<(Entity, Collider, Vec3, f32, Option<Entity>)> = vec![];
+
+    // Stop the bullets whose lifetime is over
+    for (mut movement, _transform, collider, spawnable_option, bullet_option, entity) in
+        movement_query.iter_mut()
+    {
+        if let (Some(mut spawnable), Some(bullet)) = (spawnable_option, bullet_option) {
+            if bullet.is_expired() {
+                movement.speed = 0.0;
+                spawnable.despawn = true;
+                if bullet.explosion_radius > 0.0 {
+                    explosions.push((
+                        entity,
+                        *collider,
+                        movement.transform.translation,
+                        bullet.explosion_radius,
+                        None,
+                    ));
+                }
+            }
+        }
+    }
+
     for counter in 0..((max_movement.ceil() * 2.0) as i32) {
-        for (mut movement, _transform, collider, spawnable_option, entity) in
+        for (mut movement, _transform, collider, spawnable_option, mut bullet_option, entity) in
             movement_query.iter_mut()
         {
             if movement.direction.length() > 0.0 && movement.speed > 0.0 {
@@ -115,82 +195,81 @@ fn movement_and_collision(
                 //Add the calculated step movement to the transform
                 movement.transform.translation += step_movement;
 
-                for (collider_type, collider_transform, collider_entity) in collider_query.iter() {
+                spatial_hash.query(
+                    movement.transform.translation.truncate(),
+                    movement.transform.scale.truncate(),
+                    &mut candidates,
+                );
+
+                for &index in candidates.iter() {
+                    let (collider_type, collider_transform, collider_entity) = colliders[index];
+
                     // check if entity is colliding with another entity
                     if check_collision(&movement.transform, collider_transform)
                         && collider_entity != entity
                     {
-                        //Check Collision between all different collider types
-                        match (collider, collider_type) {
-                            (Collider::Player | Collider::Enemy, Collider::Solid) => {}
-
-                            (Collider::Enemy, Collider::Enemy) => {}
+                        let response = collision_matrix.get(*collider, *collider_type);
+                        if response == CollisionResponse::Ignore {
+                            continue;
+                        }
 
-                            (Collider::Player, Collider::RoomTransition) => {
-                                if let Ok((room_pos, direction)) =
-                                    transition_query.get(collider_entity)
-                                {
-                                    ev_leave_room.send(LeaveRoomEvent(*room_pos, *direction));
-                                }
+                        // A piercing bullet hits every target only once
+                        if let Some(bullet) = bullet_option.as_deref() {
+                            if bullet.hits.contains(&collider_entity) {
+                                continue;
                             }
+                        }
 
-                            (Collider::Player, Collider::Enemy) => {
-                                movement.transform.translation -= step_movement;
-                                take_damage.send(TakeDamageEvent {
-                                    entity,
-                                    damage_entity: collider_entity,
-                                });
-                            }
+                        ev_collision.send(CollisionEvent {
+                            entity,
+                            other: collider_entity,
+                            colliders: (*collider, *collider_type),
+                            response,
+                        });
 
-                            (Collider::Enemy, Collider::Player) => {
-                                movement.transform.translation -= step_movement;
-                                take_damage.send(TakeDamageEvent {
-                                    entity: collider_entity,
-                                    damage_entity: entity,
+                        if collider.is_bullet() {
+                            let impact = bullet_option
+                                .as_deref_mut()
+                                .map_or(BulletImpact::Stop, |bullet| {
+                                    bullet.impact(response, collider_entity)
                                 });
-                            }
 
-                            (
-                                Collider::EnemyBullet | Collider::PlayerBullet,
-                                Collider::Solid | Collider::RoomTransition,
-                            ) => {
-                                if let Some(mut spawnable) = spawnable_option {
-                                    spawnable.despawn = true;
+                            match impact {
+                                BulletImpact::Pierce => continue,
+                                BulletImpact::Bounce => {
+                                    // Reflect the bullet on the axis of the step which hit the wall
+                                    movement.transform.translation -= step_movement;
+                                    if let Some(bullet) = bullet_option.as_deref_mut() {
+                                        if counter % 2 == 0 {
+                                            movement.direction.x = -movement.direction.x;
+                                            bullet.direction.x = -bullet.direction.x;
+                                        } else {
+                                            movement.direction.y = -movement.direction.y;
+                                            bullet.direction.y = -bullet.direction.y;
+                                        }
+                                    }
+                                    break;
                                 }
-                                break;
-                            }
-
-                            (Collider::EnemyBullet, Collider::Player)
-                            | (Collider::Player, Collider::EnemyBullet) => {
-                                take_damage.send(TakeDamageEvent {
-                                    entity: collider_entity,
-                                    damage_entity: entity,
-                                });
-                                if let Some(mut spawnable) = spawnable_option {
-                                    spawnable.despawn = true;
-                                }
-                            }
-
-                            (Collider::PlayerBullet, Collider::Enemy) => {
-                                take_damage.send(TakeDamageEvent {
-                                    entity: collider_entity,
-                                    damage_entity: entity,
-                                });
-                                if let Some(mut spawnable) = spawnable_option {
-                                    spawnable.despawn = true;
+                                BulletImpact::Stop => {
+                                    // Stop the bullet, so it only hits once
+                                    movement.speed = 0.0;
+                                    if let Some(mut spawnable) = spawnable_option {
+                                        spawnable.despawn = true;
+                                    }
+                                    if let Some(bullet) = bullet_option.as_deref() {
+                                        if bullet.explosion_radius > 0.0 {
+                                            explosions.push((
+                                                entity,
+                                                *collider,
+                                                movement.transform.translation,
+                                                bullet.explosion_radius,
+                                                Some(collider_entity),
+                                            ));
+                                        }
+                                    }
+                                    break;
                                 }
                             }
-
-                            (Collider::Enemy, Collider::PlayerBullet) => {
-                                take_damage.send(TakeDamageEvent {
-                                    entity,
-                                    damage_entity: collider_entity,
-                                });
-                            }
-
-                            _ => {
-                                break;
-                            }
                         }
 
                         if counter % 2 == 0 {
@@ -206,6 +285,36 @@ fn movement_and_collision(
         }
     }
 
+    // Damage everything in the radius of the explosions, except the entity which was hit directly
+    for (entity, collider, position, radius, direct_hit) in explosions {
+        spatial_hash.query(
+            position.truncate(),
+            Vec2::splat(radius * 2.0),
+            &mut candidates,
+        );
+
+        for &index in candidates.iter() {
+            let (collider_type, collider_transform, collider_entity) = colliders[index];
+            let response = collision_matrix.get(collider, *collider_type);
+
+            if response == CollisionResponse::Damage
+                && Some(collider_entity) != direct_hit
+                && collider_transform
+                    .translation
+                    .truncate()
+                    .distance(position.truncate())
+                    <= radius
+            {
+                ev_collision.send(CollisionEvent {
+                    entity,
+                    other: collider_entity,
+                    colliders: (collider, *collider_type),
+                    response,
+                });
+            }
+        }
+    }
+
     //Send event to move entity
     move_entity.send(MoveEntity {});
 }
@@ -1,20 +1,29 @@
 use crate::{
+    camera::cursor_world_position,
+    controls::{Action, ActionState},
+    highscore::RunStats,
     map::{
         calc_mid_room_pos,
         room::{LeaveRoomEvent, TransitionDirection},
         TILE_SIZE, X_MAP_LENGTH, X_ROOM_LENGTH, Y_MAP_LENGTH, Y_ROOM_LENGTH,
     },
     menu::AppState,
+    pause::Settings,
+    rng::GameRng,
+    save::RunSave,
     spawnable::{
         behavior::Health,
         behavior::Spawnable,
         behavior::TakeDamageEvent,
+        item::ItemList,
         movement::{Collider, MoveEntity, Movement},
+        status_effect::StatusEffects,
         weapon::{Weapon, WeaponList, WeaponResource, WeaponTypes},
     },
-    BLINKING_SPEED_PLAYER,
+    PlayerCamera, BLINKING_SPEED_PLAYER,
 };
 use bevy::prelude::*;
+use rand::Rng;
 
 // --- Plugin ---
 pub struct PlayerPlugin;
@@ -24,6 +33,9 @@ pub const PLAYER_HEALTH: f32 = 5.0;
 pub const PLAYER_SPEED: f32 = 20.0;
 pub const DEFAULT_INVINCIBILITY_DURATION: f32 = 240.0;
 pub const PLAYER_COLOR: Color = Color::rgb(0.0, 0.0, 1.0);
+pub const DODGE_SPEED: f32 = 45.0;
+pub const DODGE_DURATION: f32 = 15.0;
+pub const DODGE_COOLDOWN: f32 = 60.0;
 
 // --- Components ---
 /// Player component
@@ -40,6 +52,25 @@ pub struct Invincibility {
     pub duration: f32,
 }
 
+/// Dodge roll component for the player
+///
+/// * `direction` - Direction of the current roll
+/// * `duration` - Remaining frames of the current roll
+/// * `cooldown` - Remaining frames until the player can roll again, it starts after the roll
+#[derive(Component, Default)]
+pub struct DodgeRoll {
+    pub direction: Vec2,
+    pub duration: f32,
+    pub cooldown: f32,
+}
+
+impl DodgeRoll {
+    /// Check if the player is rolling
+    pub fn is_rolling(&self) -> bool {
+        self.duration > 0.0
+    }
+}
+
 // --- Execute systems ---
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
@@ -50,7 +81,9 @@ impl Plugin for PlayerPlugin {
                 SystemSet::on_update(AppState::InGame)
                     .label("step1")
                     .with_system(player_shooting)
+                    .with_system(player_switch_weapon)
                     .with_system(player_movement_input)
+                    .with_system(player_dodge)
                     .with_system(room_transmit),
             )
             .add_system_set(
@@ -62,87 +95,205 @@ impl Plugin for PlayerPlugin {
 
 // --- System-Functions ---
 /// Spawns the player at the center of the map
-fn spawn_player(mut commands: Commands, weapon_res: Res<WeaponResource>) {
-    let random_weapon: WeaponTypes = rand::random();
+///
+/// If a saved run is continued, the saved player is spawned instead
+fn spawn_player(
+    mut commands: Commands,
+    weapon_res: Res<WeaponResource>,
+    mut rng: ResMut<GameRng>,
+    resume: Option<Res<RunSave>>,
+) {
+    if let Some(save) = resume {
+        spawn_player_entity(
+            &mut commands,
+            save.player.position,
+            Player {
+                score: save.player.score,
+                speed: save.player.speed,
+                color: PLAYER_COLOR,
+            },
+            save.player.health.clone(),
+            save.player.weapons.clone(),
+            ItemList {
+                items: save.player.items.clone(),
+            },
+        );
+        commands.insert_resource(save.stats.clone());
+        return;
+    }
+
+    let random_weapon: WeaponTypes = rng.gen();
     let weaponlist: Vec<Weapon> = vec![weapon_res.weapons[&random_weapon].clone()];
+    commands.insert_resource(RunStats {
+        starting_weapon: weaponlist[0].name.clone(),
+        ..Default::default()
+    });
 
     let curr_room_pos = calc_mid_room_pos(X_MAP_LENGTH / 2, Y_MAP_LENGTH / 2);
 
-    commands
-        .spawn_bundle(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(curr_room_pos.x, curr_room_pos.y, 4.0),
-                scale: Vec3::new(25.0, 25.0, 0.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: PLAYER_COLOR,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Player {
+    spawn_player_entity(
+        &mut commands,
+        curr_room_pos,
+        Player {
             score: 0,
             speed: PLAYER_SPEED,
             color: PLAYER_COLOR,
-        })
-        .insert(Collider::Player)
-        .insert(Health {
+        },
+        Health {
             health: PLAYER_HEALTH,
             max_health: PLAYER_HEALTH,
-        })
-        .insert(WeaponList {
+        },
+        WeaponList {
             weapons: weaponlist,
-        })
-        .insert(Spawnable {
-            on_despawn: vec![],
-            despawn: false,
-        });
+            active: 0,
+        },
+        ItemList { items: vec![] },
+    );
 }
 
-/// Shoots a weapon with the WASD keys
+/// Shoots a weapon with the shoot actions
+///
+/// Digital inputs shoot along one axis, the vertical one first, while sticks aim in any direction.
+/// With mouse aim turned on, the shoot action shoots at the cursor.
+/// Status effects change the fire rate.
 fn player_shooting(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mut player_query: Query<(&Transform, &mut WeaponList), With<Player>>,
-    mut time: Res<Time>,
+    (actions, settings, windows): (Res<ActionState>, Res<Settings>, Res<Windows>),
+    mut player_query: Query<(&Transform, &mut WeaponList, &StatusEffects), With<Player>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let (transform, mut weaponlist) = player_query.single_mut();
-    let mut dir: Vec2 = Vec2::new(0.0, 0.0);
+    let (transform, mut weaponlist, status_effects) = player_query.single_mut();
+    let mut dir: Vec2 = actions.direction(
+        Action::ShootUp,
+        Action::ShootDown,
+        Action::ShootLeft,
+        Action::ShootRight,
+    );
+
+    if dir.x.abs() == 1.0 && dir.y.abs() == 1.0 {
+        dir.x = 0.0;
+    }
 
-    if keys.any_pressed([KeyCode::W, KeyCode::S]) {
-        dir.y += (keys.pressed(KeyCode::W) as i32 - keys.pressed(KeyCode::S) as i32) as f32;
-    } else if keys.any_pressed([KeyCode::A, KeyCode::D]) {
-        dir.x += (keys.pressed(KeyCode::D) as i32 - keys.pressed(KeyCode::A) as i32) as f32;
+    if settings.mouse_aim && actions.pressed(Action::Shoot) {
+        let cursor = windows
+            .get_primary()
+            .zip(camera_query.get_single().ok())
+            .and_then(|(window, (camera, camera_transform))| {
+                cursor_world_position(window, camera, camera_transform)
+            });
+        if let Some(cursor) = cursor {
+            dir = cursor - transform.translation.truncate();
+        }
     }
 
-    weaponlist.weapons[0].shoot_weapon(
+    weaponlist.active_weapon_mut().shoot_weapon(
         &mut commands,
-        &mut time,
+        time.delta_seconds() * status_effects.fire_rate_factor(),
         dir,
         Vec2::new(transform.translation.x, transform.translation.y),
         true,
+        &mut rng,
     )
 }
 
-/// Moves the player with Arrow keys
+/// Switches the active weapon with the weapon actions
+fn player_switch_weapon(
+    actions: Res<ActionState>,
+    mut player_query: Query<&mut WeaponList, With<Player>>,
+) {
+    let mut weaponlist = player_query.single_mut();
+
+    if actions.just_pressed(Action::PreviousWeapon) {
+        weaponlist.switch_weapon(-1);
+    } else if actions.just_pressed(Action::NextWeapon) {
+        weaponlist.switch_weapon(1);
+    }
+}
+
+/// Moves the player with the move actions, a rolling player keeps the direction of the roll
+///
+/// Status effects change the speed of walking and rolling.
 fn player_movement_input(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mut player_query: Query<(&Player, &Transform, Entity)>,
+    actions: Res<ActionState>,
+    mut player_query: Query<(&Player, &DodgeRoll, &StatusEffects, &Transform, Entity)>,
 ) {
-    let (player, transform, player_entity) = player_query.single_mut();
-    let mut dir: Vec2 = Vec2::new(0.0, 0.0);
+    let (player, dodge_roll, status_effects, transform, player_entity) = player_query.single_mut();
+    let speed_factor = status_effects.speed_factor();
+
+    if dodge_roll.is_rolling() {
+        commands.entity(player_entity).insert(Movement {
+            direction: dodge_roll.direction,
+            transform: *transform,
+            speed: DODGE_SPEED * speed_factor,
+        });
+        return;
+    }
 
     // check direction input
-    dir.y += (keys.pressed(KeyCode::Up) as i32 - keys.pressed(KeyCode::Down) as i32) as f32;
-    dir.x += (keys.pressed(KeyCode::Right) as i32 - keys.pressed(KeyCode::Left) as i32) as f32;
+    let dir: Vec2 = actions.direction(
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+    );
 
     if dir.length() != 0.0 {
         commands.entity(player_entity).insert(Movement {
             direction: dir,
             transform: *transform,
-            speed: player.speed,
+            speed: player.speed * speed_factor,
+        });
+    }
+}
+
+/// Starts a dodge roll in the direction of the movement and ends it after its duration
+///
+/// The rolling player is invincible and uses the rolling collider, so enemy bullets pass through.
+fn player_dodge(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut player_query: Query<
+        (
+            &mut DodgeRoll,
+            &mut Collider,
+            Option<&Invincibility>,
+            Entity,
+        ),
+        With<Player>,
+    >,
+) {
+    let (mut dodge_roll, mut collider, invincibility, entity) = player_query.single_mut();
+
+    if dodge_roll.is_rolling() {
+        dodge_roll.duration -= 1.0;
+        if !dodge_roll.is_rolling() {
+            *collider = Collider::Player;
+        }
+        return;
+    }
+
+    dodge_roll.cooldown = f32::max(dodge_roll.cooldown - 1.0, 0.0);
+
+    let dir: Vec2 = actions.direction(
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+    );
+
+    if actions.just_pressed(Action::Dodge) && dodge_roll.cooldown <= 0.0 && dir.length() != 0.0 {
+        dodge_roll.direction = dir.normalize();
+        dodge_roll.duration = DODGE_DURATION;
+        dodge_roll.cooldown = DODGE_COOLDOWN;
+        *collider = Collider::RollingPlayer;
+
+        // Don't shorten the invincibility of a hit
+        let duration = invincibility.map_or(0.0, |invincibility| invincibility.duration);
+        commands.entity(entity).insert(Invincibility {
+            duration: f32::max(duration, DODGE_DURATION),
         });
     }
 }
@@ -200,3 +351,47 @@ fn check_invincibility(
         }
     }
 }
+
+// --- Functions ---
+/// Spawns the player entity
+///
+/// # Arguments
+/// * `commands` - Commands to spawn the player
+/// * `position` - Position of the player
+/// * `player` - Player component with score and speed
+/// * `health` - Health of the player
+/// * `weapon_list` - Weapons of the player
+/// * `item_list` - Collected items of the player
+fn spawn_player_entity(
+    commands: &mut Commands,
+    position: Vec2,
+    player: Player,
+    health: Health,
+    weapon_list: WeaponList,
+    item_list: ItemList,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: position.extend(4.0),
+                scale: Vec3::new(25.0, 25.0, 0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: player.color,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(player)
+        .insert(Collider::Player)
+        .insert(DodgeRoll::default())
+        .insert(StatusEffects::default())
+        .insert(health)
+        .insert(weapon_list)
+        .insert(item_list)
+        .insert(Spawnable {
+            on_despawn: vec![],
+            despawn: false,
+        });
+}
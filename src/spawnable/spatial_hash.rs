@@ -0,0 +1,112 @@
+// --- Imports ---
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+// --- Structs ---
+/// Uniform grid to find the colliders near a position without checking every collider
+///
+/// Colliders are stored by their index in a list of the caller, every cell which is overlapped by the
+/// bounding box of a collider contains its index.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Create an empty spatial hash
+    ///
+    /// # Arguments
+    /// * `cell_size` - The width and height of a cell
+    pub fn new(cell_size: f32) -> SpatialHash {
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Insert a collider into all cells overlapped by its bounding box
+    ///
+    /// # Arguments
+    /// * `index` - The index of the collider
+    /// * `position` - The center of the bounding box
+    /// * `size` - The size of the bounding box
+    pub fn insert(&mut self, index: usize, position: Vec2, size: Vec2) {
+        let (min, max) = self.cell_range(position, size);
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Find the colliders whose cells are overlapped by a bounding box
+    ///
+    /// The candidates are sorted by their index and contain every collider only once.
+    ///
+    /// # Arguments
+    /// * `position` - The center of the bounding box
+    /// * `size` - The size of the bounding box
+    /// * `candidates` - Buffer for the found indices, it is cleared before the search
+    pub fn query(&self, position: Vec2, size: Vec2, candidates: &mut Vec<usize>) {
+        candidates.clear();
+
+        let (min, max) = self.cell_range(position, size);
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(indices) = self.cells.get(&(x, y)) {
+                    candidates.extend(indices);
+                }
+            }
+        }
+
+        candidates.sort_unstable();
+        candidates.dedup();
+    }
+
+    /// Get the first and the last cell overlapped by a bounding box
+    fn cell_range(&self, position: Vec2, size: Vec2) -> ((i32, i32), (i32, i32)) {
+        let min = (position - size / 2.0) / self.cell_size;
+        let max = (position + size / 2.0) / self.cell_size;
+
+        (
+            (min.x.floor() as i32, min.y.floor() as i32),
+            (max.x.floor() as i32, max.y.floor() as i32),
+        )
+    }
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if only the colliders near the position are found
+    #[test]
+    fn test_query_nearby() {
+        let mut spatial_hash = SpatialHash::new(10.0);
+        spatial_hash.insert(0, Vec2::new(5.0, 5.0), Vec2::new(2.0, 2.0));
+        spatial_hash.insert(1, Vec2::new(55.0, 5.0), Vec2::new(2.0, 2.0));
+        spatial_hash.insert(2, Vec2::new(15.0, 5.0), Vec2::new(2.0, 2.0));
+
+        let mut candidates = vec![];
+        spatial_hash.query(Vec2::new(10.0, 5.0), Vec2::new(4.0, 4.0), &mut candidates);
+
+        assert_eq!(candidates, vec![0, 2]);
+    }
+
+    /// Test if large colliders are found from every cell they overlap, but only once
+    #[test]
+    fn test_query_large_collider() {
+        let mut spatial_hash = SpatialHash::new(10.0);
+        spatial_hash.insert(3, Vec2::new(50.0, 0.0), Vec2::new(100.0, 10.0));
+        spatial_hash.insert(1, Vec2::new(-50.0, 0.0), Vec2::new(2.0, 2.0));
+
+        let mut candidates = vec![7];
+        spatial_hash.query(Vec2::new(95.0, 0.0), Vec2::new(2.0, 2.0), &mut candidates);
+        assert_eq!(candidates, vec![3]);
+
+        spatial_hash.query(Vec2::new(0.0, 0.0), Vec2::new(120.0, 2.0), &mut candidates);
+        assert_eq!(candidates, vec![1, 3]);
+    }
+}
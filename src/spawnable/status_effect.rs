@@ -0,0 +1,246 @@
+// --- Imports ---
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    menu::AppState,
+    spawnable::{behavior::Health, enemy::behavior::Enemy, player::Player},
+};
+
+// --- Plugin declaration ---
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(update_status_effects.after("step3")),
+        );
+    }
+}
+
+// --- Components and Structs ---
+/// Kind of a status effect, every kind has its own tint
+///
+/// * `Burn` - Usually deals tick damage
+/// * `Freeze` - Usually stops the movement and slows the fire rate
+/// * `Poison` - Usually deals tick damage and stacks
+/// * `Slow` - Usually slows the movement
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    Burn,
+    Freeze,
+    Poison,
+    Slow,
+}
+
+/// Status effect which is applied by bullets and enemy contact (values are defined in the ron files)
+///
+/// * `kind` - Kind of the effect, effects of the same kind stack
+/// * `duration` - Seconds the effect lasts, applying it again refreshes the duration
+/// * `damage` - Damage per second of every stack
+/// * `max_stacks` - Maximum number of stacks
+/// * `speed_factor` - Factor of the movement speed while the effect is active
+/// * `fire_rate_factor` - Factor of the fire rate while the effect is active
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32,
+    #[serde(default)]
+    pub damage: f32,
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: u32,
+    #[serde(default = "default_factor")]
+    pub speed_factor: f32,
+    #[serde(default = "default_factor")]
+    pub fire_rate_factor: f32,
+}
+
+/// Active status effect with its stacks and remaining duration
+#[derive(Clone, Debug)]
+pub struct ActiveEffect {
+    pub effect: StatusEffect,
+    pub stacks: u32,
+    pub remaining: f32,
+}
+
+/// Status effects of the player or an enemy
+///
+/// * `effects` - The active effects, at most one per kind
+/// * `tint` - The tint which was last applied to the sprite
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<ActiveEffect>,
+    tint: Option<Color>,
+}
+
+// --- Methods for Structs ---
+impl StatusKind {
+    /// Get the tint of the sprite while the effect is active
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusKind::Burn => Color::rgb(1.0, 0.5, 0.0),
+            StatusKind::Freeze => Color::rgb(0.6, 0.9, 1.0),
+            StatusKind::Poison => Color::rgb(0.3, 0.8, 0.1),
+            StatusKind::Slow => Color::rgb(0.6, 0.3, 0.8),
+        }
+    }
+}
+
+impl StatusEffects {
+    /// Check if a color is the tint which the status effects applied to the sprite
+    pub fn shows_tint(&self, color: Color) -> bool {
+        matches!(self.tint, Some(tint) if same_rgb(color, tint))
+    }
+
+    /// Apply a status effect
+    ///
+    /// An effect of a kind which is already active adds a stack up to its maximum and refreshes the duration.
+    ///
+    /// # Arguments
+    /// * `effect` - The effect to apply
+    pub fn apply(&mut self, effect: &StatusEffect) {
+        match self
+            .effects
+            .iter_mut()
+            .find(|active| active.effect.kind == effect.kind)
+        {
+            Some(active) => {
+                active.stacks = u32::min(active.stacks + 1, active.effect.max_stacks.max(1));
+                active.remaining = f32::max(active.remaining, effect.duration);
+            }
+            None => self.effects.push(ActiveEffect {
+                effect: effect.clone(),
+                stacks: 1,
+                remaining: effect.duration,
+            }),
+        }
+    }
+
+    /// Count down the effects and remove the expired ones
+    ///
+    /// # Arguments
+    /// * `delta` - Seconds since the last update
+    ///
+    /// # Returns
+    /// The tick damage of all stacks in this update
+    pub fn tick(&mut self, delta: f32) -> f32 {
+        let mut damage = 0.0;
+        for active in self.effects.iter_mut() {
+            let time = f32::min(delta, active.remaining);
+            damage += active.effect.damage * active.stacks as f32 * time;
+            active.remaining -= delta;
+        }
+
+        self.effects.retain(|active| active.remaining > 0.0);
+        damage
+    }
+
+    /// Get the factor of the movement speed of all active effects
+    pub fn speed_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|active| active.effect.speed_factor)
+            .product()
+    }
+
+    /// Get the factor of the fire rate of all active effects
+    pub fn fire_rate_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|active| active.effect.fire_rate_factor)
+            .product()
+    }
+}
+
+// --- System-Functions ---
+/// Deals the tick damage of the status effects and tints the sprites of affected entities
+///
+/// The tint is only applied if no other color is shown (like the hitstun, the invincibility or a telegraphed attack),
+/// the base color is restored after the effects are over.
+fn update_status_effects(
+    time: Res<Time>,
+    mut query: Query<(&mut StatusEffects, &mut Health, &mut Sprite, Entity)>,
+    player_query: Query<&Player>,
+    enemy_query: Query<&Enemy>,
+) {
+    for (mut status_effects, mut health, mut sprite, entity) in query.iter_mut() {
+        if status_effects.effects.is_empty() && status_effects.tint.is_none() {
+            continue;
+        }
+
+        let damage = status_effects.tick(time.delta_seconds());
+        if damage > 0.0 {
+            health.take_damage(damage);
+        }
+
+        let base_color = match (player_query.get(entity), enemy_query.get(entity)) {
+            (Ok(player), _) => player.color,
+            (Err(_), Ok(enemy)) => enemy.color,
+            _ => continue,
+        };
+
+        // Don't override a color of another system
+        let shows_base = same_rgb(sprite.color, base_color);
+        let shows_tint = status_effects.shows_tint(sprite.color);
+        if !shows_base && !shows_tint {
+            continue;
+        }
+
+        // The most recent effect is shown
+        let tint = status_effects
+            .effects
+            .last()
+            .map(|active| active.effect.kind.tint());
+        let alpha = sprite.color.a();
+        sprite.color = tint.unwrap_or(base_color);
+        sprite.color.set_a(alpha);
+        status_effects.tint = tint;
+    }
+}
+
+// --- Functions ---
+/// Default maximum stacks of a status effect
+fn default_max_stacks() -> u32 {
+    1
+}
+
+/// Default speed and fire rate factor of a status effect
+fn default_factor() -> f32 {
+    1.0
+}
+
+/// Check if two colors have the same red, green and blue values
+pub fn same_rgb(a: Color, b: Color) -> bool {
+    a.r() == b.r() && a.g() == b.g() && a.b() == b.b()
+}
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if applying an effect again stacks up to the maximum and refreshes the duration
+    #[test]
+    fn test_stacking_and_tick_damage() {
+        let poison = StatusEffect {
+            kind: StatusKind::Poison,
+            duration: 2.0,
+            damage: 1.0,
+            max_stacks: 2,
+            speed_factor: 1.0,
+            fire_rate_factor: 1.0,
+        };
+        let mut status_effects = StatusEffects::default();
+
+        for _ in 0..3 {
+            status_effects.apply(&poison);
+        }
+        assert_eq!(status_effects.effects.len(), 1);
+        assert_eq!(status_effects.effects[0].stacks, 2);
+
+        assert_eq!(status_effects.tick(1.0), 2.0);
+        // Only the remaining second of the effect deals damage
+        assert_eq!(status_effects.tick(1.5), 2.0);
+        assert!(status_effects.effects.is_empty());
+    }
+}
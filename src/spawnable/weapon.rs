@@ -1,18 +1,39 @@
 use std::collections::HashMap;
 
 use crate::{
+    map::map_generation::RoomPos,
     menu::AppState,
+    rng::GameRng,
     spawnable::{
-        behavior::DespawnBehavior, behavior::Spawnable, bullet::Bullet, movement::Collider,
+        behavior::DespawnBehavior,
+        behavior::Spawnable,
+        bullet::Bullet,
+        item::{ItemList, ItemResource},
+        movement::{check_collision, Collider},
+        player::Player,
+        status_effect::StatusEffect,
     },
 };
 
-use bevy::prelude::*;
-use rand::{distributions::Standard, prelude::Distribution, thread_rng, Rng};
-use serde::Deserialize;
+use bevy::{prelude::*, reflect::TypeUuid};
+use rand::{distributions::Standard, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
 
 pub struct WeaponPlugin;
 
+// --- Constants ---
+/// Maximum number of weapons the player can carry
+pub const MAX_WEAPONS: usize = 3;
+/// Chance that a finished normal room drops a weapon
+pub const WEAPON_DROP_CHANCE: f32 = 0.15;
+/// Weapons the player can start with or pick up
+pub const PLAYER_WEAPONS: [WeaponTypes; 4] = [
+    WeaponTypes::PlayerPistol,
+    WeaponTypes::PlayerSniper,
+    WeaponTypes::PlayerShotgun,
+    WeaponTypes::PlayerMachineGun,
+];
+
 // --- Execute systems ---
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
@@ -20,32 +41,38 @@ impl Plugin for WeaponPlugin {
             SystemSet::on_update(AppState::InGame)
                 .with_system(reload_weapon)
                 .before("step2"),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::InGame).with_system(pickup_weapon.after("step3")),
         );
     }
 }
 
 // --- Components and Structs ---
 /// Weapon-Resource for loading weapons from ron file
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, TypeUuid)]
+#[uuid = "8a106d71-e977-4c0d-8511-659ff0c4c1fd"]
 pub struct WeaponResource {
     pub weapons: HashMap<WeaponTypes, Weapon>,
 }
 
 /// Maximum and current values for reload, ammo and magazine
-#[derive(Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct MaxCurrent {
     pub max: f32,
     pub current: f32,
 }
 
-/// List of weapons
-#[derive(Deserialize, Component, Clone)]
+/// List of weapons with the index of the active weapon
+#[derive(Serialize, Deserialize, Component, Clone)]
 pub struct WeaponList {
     pub weapons: Vec<Weapon>,
+    #[serde(default)]
+    pub active: usize,
 }
 
 /// Weapon-Component
-#[derive(Deserialize, Component, Clone)]
+#[derive(Serialize, Deserialize, Component, Clone)]
 pub struct Weapon {
     pub name: String,
     pub clip_size: MaxCurrent,
@@ -58,10 +85,37 @@ pub struct Weapon {
     pub hitstun_duration: f32,
     pub spread: f32,
     pub shooting_pattern: Vec<f32>,
+    #[serde(default)]
+    pub projectile: ProjectileModifiers,
+    #[serde(default)]
+    pub effects: Vec<StatusEffect>,
+}
+
+/// Optional modifiers of the bullets of a weapon, modifiers which aren't set have no effect
+///
+/// * `pierce` - Number of targets a bullet passes through before it stops
+/// * `bounce` - Number of times a bullet bounces off walls
+/// * `homing` - Strength of the turn toward the nearest target in radians per second, player bullets follow enemies and enemy bullets follow the player
+/// * `explosion_radius` - Radius of the explosion when a bullet stops, everything in it which the bullet could damage takes its damage
+/// * `lifetime` - Time in seconds until a bullet stops, which limits its range, 0 for no limit
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ProjectileModifiers {
+    pub pierce: u32,
+    pub bounce: u32,
+    pub homing: f32,
+    pub explosion_radius: f32,
+    pub lifetime: f32,
+}
+
+/// Weapon pickup which can be collected by the player
+#[derive(Component)]
+pub struct WeaponPickup {
+    pub weapon_type: WeaponTypes,
 }
 
 /// Enum for weapon types (values are defined in the ron file)
-#[derive(Component, Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum WeaponTypes {
     None,
     Pistol,
@@ -81,13 +135,43 @@ pub enum WeaponTypes {
 impl Distribution<WeaponTypes> for Standard {
     /// Returns a random player-weapon for the player
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> WeaponTypes {
-        match rng.gen_range(0..=3) {
-            0 => WeaponTypes::PlayerPistol,
-            1 => WeaponTypes::PlayerSniper,
-            2 => WeaponTypes::PlayerShotgun,
-            _ => WeaponTypes::PlayerMachineGun,
+        PLAYER_WEAPONS[rng.gen_range(0..PLAYER_WEAPONS.len())].clone()
+    }
+}
+
+impl WeaponList {
+    /// Returns the active weapon
+    pub fn active_weapon(&self) -> &Weapon {
+        &self.weapons[self.active]
+    }
+
+    /// Returns the active weapon as mutable
+    pub fn active_weapon_mut(&mut self) -> &mut Weapon {
+        &mut self.weapons[self.active]
+    }
+
+    /// Switches the active weapon by the given offset and wraps around at the end of the list
+    pub fn switch_weapon(&mut self, offset: i32) {
+        let length = self.weapons.len() as i32;
+        self.active = (self.active as i32 + offset).rem_euclid(length) as usize;
+    }
+
+    /// Adds a weapon to the list and makes it active
+    ///
+    /// If the list is full, the active weapon is replaced
+    pub fn add_weapon(&mut self, weapon: Weapon) {
+        if self.weapons.len() < MAX_WEAPONS {
+            self.weapons.push(weapon);
+            self.active = self.weapons.len() - 1;
+        } else {
+            self.weapons[self.active] = weapon;
         }
     }
+
+    /// Checks if the list contains a weapon with the given name
+    pub fn contains(&self, name: &str) -> bool {
+        self.weapons.iter().any(|weapon| weapon.name == name)
+    }
 }
 
 impl Weapon {
@@ -99,6 +183,7 @@ impl Weapon {
         start_x: f32,
         start_y: f32,
         is_player: bool,
+        rng: &mut GameRng,
     ) {
         // Get the color of the bullet depending on if it is a player bullet or not
         let (bullet_color, bullet_type) = if is_player {
@@ -107,36 +192,17 @@ impl Weapon {
             (Color::rgb(0.8, 0.0, 0.0), Collider::EnemyBullet)
         };
 
+        let direction = direction.normalize_or_zero();
+
         // Iterate through the shooting patterns and spawn a bullet for each one
         for angle in self.shooting_pattern.iter() {
             // Calculate the bullet's angle
-            let mut rng = thread_rng();
             let mut angle_modifier = *angle;
             if self.spread != 0.0 {
                 angle_modifier += rng.gen_range(-self.spread..self.spread);
             }
 
-            let direction_angle =
-                ((direction.x.abs() / direction.y).atan() + angle_modifier) % core::f32::consts::PI;
-
-            let mut modifier = 1.0;
-
-            if (direction_angle - angle_modifier).signum() != direction_angle.signum() {
-                modifier = -modifier;
-            }
-
-            let bullet_direction;
-            if direction.x != 0.0 {
-                bullet_direction =
-                    Vec2::new(1.0 * direction.x.signum(), 1.0 / direction_angle.tan()) * modifier;
-            } else if direction_angle == 0.0 {
-                bullet_direction = Vec2::new(0.0, direction.y.signum());
-            } else {
-                bullet_direction = Vec2::new(
-                    1.0 * direction.x.signum(),
-                    1.0 / direction_angle.tan() * direction.y.signum(),
-                ) * modifier;
-            }
+            let bullet_direction = rotate_clockwise(direction, angle_modifier);
 
             // Spawn the bullet
             commands
@@ -160,6 +226,17 @@ impl Weapon {
                     knockback_strength: self.knockback_strength,
                     knockback_duration: self.knockback_duration,
                     hitstun_duration: self.hitstun_duration,
+                    pierce: self.projectile.pierce,
+                    bounce: self.projectile.bounce,
+                    homing: self.projectile.homing,
+                    explosion_radius: self.projectile.explosion_radius,
+                    lifetime: if self.projectile.lifetime > 0.0 {
+                        Some(self.projectile.lifetime)
+                    } else {
+                        None
+                    },
+                    hits: vec![],
+                    effects: self.effects.clone(),
                 })
                 .insert(bullet_type)
                 .insert(Spawnable {
@@ -170,20 +247,23 @@ impl Weapon {
     }
 
     /// Checks if the weapon is ready to fire and if so, spawns a bullet
+    ///
+    /// The fire rate is counted down with `delta`, so status effects can slow it by scaling the seconds since the last frame
     pub fn shoot_weapon(
         &mut self,
         commands: &mut Commands,
-        time: &mut Res<Time>,
+        delta: f32,
         direction: Vec2,
         start: Vec2,
         is_player: bool,
+        rng: &mut GameRng,
     ) {
         if self.reload_time.current <= 0.0 {
-            self.fire_rate.current -= time.delta_seconds();
+            self.fire_rate.current -= delta;
 
             if direction.length() > 0.0 && self.fire_rate.current <= 0.0 {
                 if self.clip_size.current > 0.0 || self.clip_size.max == 0.0 {
-                    self.spawn_bullet(commands, direction, start.x, start.y, is_player);
+                    self.spawn_bullet(commands, direction, start.x, start.y, is_player, rng);
 
                     if self.clip_size.max > 0.0 {
                         self.clip_size.current -= 1.0;
@@ -204,7 +284,82 @@ impl Weapon {
     }
 }
 
+// --- Functions ---
+/// Spawns a weapon pickup at the given position
+pub fn spawn_weapon_pickup(
+    commands: &mut Commands,
+    weapon_res: &WeaponResource,
+    weapon_type: WeaponTypes,
+    position: Vec2,
+    room_pos: RoomPos,
+) -> Entity {
+    let weapon = &weapon_res.weapons[&weapon_type];
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: position.extend(1.0),
+                scale: Vec3::new(30.0, 12.0, 0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: Color::rgb(0.8, 0.8, 0.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(WeaponPickup { weapon_type })
+        .insert(Name::new(weapon.name.clone()))
+        .insert(room_pos)
+        .id()
+}
+
+/// Rotates a direction clockwise, so positive angles of a shooting pattern turn right from the shooting direction
+///
+/// # Arguments
+/// * `direction` - The direction to rotate
+/// * `angle` - The angle in radians
+pub fn rotate_clockwise(direction: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    Vec2::new(
+        direction.x * cos + direction.y * sin,
+        direction.y * cos - direction.x * sin,
+    )
+}
+
 // --- System-Functions ---
+/// Collects the weapons the player touches, if they are not carried already
+///
+/// The modifiers of the collected items are applied to the new weapon
+fn pickup_weapon(
+    mut commands: Commands,
+    weapon_res: Res<WeaponResource>,
+    item_res: Res<ItemResource>,
+    weapon_query: Query<(&WeaponPickup, &Transform, Entity), Without<Player>>,
+    mut player_query: Query<(&mut WeaponList, &ItemList, &Transform), With<Player>>,
+) {
+    let (mut weapon_list, item_list, player_transform) = player_query.single_mut();
+
+    for (weapon_pickup, weapon_transform, entity) in weapon_query.iter() {
+        let mut weapon = weapon_res.weapons[&weapon_pickup.weapon_type].clone();
+
+        if check_collision(player_transform, weapon_transform)
+            && !weapon_list.contains(&weapon.name)
+        {
+            for item in item_list
+                .items
+                .iter()
+                .filter_map(|item_type| item_res.items.get(item_type))
+            {
+                item.apply_to_weapon(&mut weapon);
+            }
+            weapon_list.add_weapon(weapon);
+
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 /// Automatically reloads the weapon if it empty
 fn reload_weapon(mut weapon_query: Query<&mut WeaponList>, time: Res<Time>) {
     for mut weapon_list in weapon_query.iter_mut() {
@@ -215,3 +370,22 @@ fn reload_weapon(mut weapon_query: Query<&mut WeaponList>, time: Res<Time>) {
         }
     }
 }
+
+mod tests {
+    #![allow(unused_imports)]
+    use super::*;
+
+    /// Test if shooting patterns rotate around diagonal directions like around axis-aligned ones
+    #[test]
+    fn test_rotate_clockwise() {
+        let up = rotate_clockwise(Vec2::Y, std::f32::consts::FRAC_PI_2);
+        assert!(up.abs_diff_eq(Vec2::X, 1e-6));
+
+        let diagonal = Vec2::new(1.0, 1.0).normalize();
+        let rotated = rotate_clockwise(diagonal, std::f32::consts::FRAC_PI_4);
+        assert!(rotated.abs_diff_eq(Vec2::X, 1e-6));
+
+        let rotated = rotate_clockwise(diagonal, -std::f32::consts::FRAC_PI_4);
+        assert!(rotated.abs_diff_eq(Vec2::Y, 1e-6));
+    }
+}